@@ -0,0 +1,286 @@
+use crate::scheduler::human_time;
+use crate::task::Task;
+use crate::timewarrior::{self, Interval};
+use chrono::{DateTime, Duration, Utc};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
+
+/// How few finished tasks we need in a project before we trust its
+/// correction factor over the overall one.
+const MIN_SAMPLES: usize = 3;
+
+/// How long finished tasks actually took compared to their estimates.
+#[derive(Debug, Default)]
+pub struct Accuracy {
+    pub by_project: BTreeMap<String, Group>,
+    pub by_tag: BTreeMap<String, Group>,
+    pub overall: Group,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Group {
+    pub count: usize,
+    pub estimated: Duration,
+    pub actual: Duration,
+}
+
+impl Group {
+    fn add(&mut self, estimated: Duration, actual: Duration) {
+        self.count += 1;
+        self.estimated += estimated;
+        self.actual += actual;
+    }
+
+    /// How many times longer tasks took than we thought they would.
+    pub fn ratio(&self) -> Option<f64> {
+        if self.estimated <= Duration::zero() {
+            return None;
+        }
+
+        Some(self.actual.num_seconds() as f64 / self.estimated.num_seconds() as f64)
+    }
+}
+
+impl Accuracy {
+    /// Compare estimates against tracked time for finished tasks. Tasks
+    /// without an estimate or without any tracked time are skipped, since
+    /// there's nothing to compare.
+    pub fn new(tasks: &[Task], intervals: &[Interval], now: DateTime<Utc>) -> Self {
+        let mut out = Self::default();
+
+        for task in tasks {
+            let Some(estimated) = task.estimate else {
+                continue;
+            };
+
+            let actual = timewarrior::time_spent(intervals, task, now);
+            if actual <= Duration::zero() {
+                continue;
+            }
+
+            out.overall.add(estimated, actual);
+
+            // Subprojects count towards their parents too, so `work.client`
+            // informs the correction for `work`.
+            if let Some(project) = &task.project {
+                let mut parts = project.split('.').peekable();
+                let mut prefix = String::new();
+                while let Some(part) = parts.next() {
+                    prefix.push_str(part);
+                    out.by_project
+                        .entry(prefix.clone())
+                        .or_default()
+                        .add(estimated, actual);
+                    if parts.peek().is_some() {
+                        prefix.push('.');
+                    }
+                }
+            }
+
+            for tag in &task.tags {
+                out.by_tag
+                    .entry(tag.clone())
+                    .or_default()
+                    .add(estimated, actual);
+            }
+        }
+
+        out
+    }
+
+    pub fn corrections(&self) -> Corrections {
+        let trusted = |group: &Group| Some(group).filter(|g| g.count >= MIN_SAMPLES)?.ratio();
+
+        Corrections {
+            by_project: self
+                .by_project
+                .iter()
+                .filter_map(|(project, group)| Some((project.clone(), trusted(group)?)))
+                .collect(),
+            by_tag: self
+                .by_tag
+                .iter()
+                .filter_map(|(tag, group)| Some((tag.clone(), (trusted(group)?, group.count))))
+                .collect(),
+            overall: trusted(&self.overall),
+        }
+    }
+}
+
+impl Display for Accuracy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<8}{:<12}{:<12}{:<8}Group",
+            "Tasks", "Estimated", "Actual", "Ratio"
+        )?;
+        write_group(f, &self.overall, "(all)")?;
+
+        for (heading, groups) in [("Projects", &self.by_project), ("Tags", &self.by_tag)] {
+            if groups.is_empty() {
+                continue;
+            }
+
+            writeln!(f, "\n{heading}")?;
+            for (name, group) in groups {
+                write_group(f, group, name)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn write_group(f: &mut std::fmt::Formatter<'_>, group: &Group, name: &str) -> std::fmt::Result {
+    let ratio = group
+        .ratio()
+        .map(|ratio| format!("{ratio:.2}x"))
+        .unwrap_or_else(|| "-".into());
+
+    writeln!(
+        f,
+        "{:<8}{:<12}{:<12}{:<8}{name}",
+        group.count,
+        human_time(group.estimated),
+        human_time(group.actual),
+        ratio,
+    )
+}
+
+/// Factors to multiply estimates by, learned from how long tasks actually
+/// took.
+#[derive(Debug, Default, Clone)]
+pub struct Corrections {
+    by_project: HashMap<String, f64>,
+    /// Factors for tags, with how many tasks they came from.
+    by_tag: BTreeMap<String, (f64, usize)>,
+    overall: Option<f64>,
+}
+
+impl Corrections {
+    /// Use the most specific project we have enough data for, falling back
+    /// to parent projects. Tasks without one use the tag with the most
+    /// finished tasks behind it, and then everything.
+    pub fn factor_for(&self, task: &Task) -> f64 {
+        let mut project = task.project.as_deref();
+
+        while let Some(current) = project {
+            if let Some(factor) = self.by_project.get(current) {
+                return *factor;
+            }
+
+            project = current.rsplit_once('.').map(|(parent, _)| parent);
+        }
+
+        // Ties go to the tag that sorts first, so the factor doesn't depend
+        // on what order the tags came in.
+        let tag = self
+            .by_tag
+            .iter()
+            .filter(|(tag, _)| task.tags.contains(*tag))
+            .rev()
+            .max_by_key(|(_, (_, count))| *count);
+        if let Some((_, (factor, _))) = tag {
+            return *factor;
+        }
+
+        self.overall.unwrap_or(1.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_support::TaskBuilder;
+
+    fn task(uuid: &str, project: &str, hours: i64) -> Task {
+        TaskBuilder::new(uuid)
+            .with_project(project)
+            .with_estimate(Duration::hours(hours))
+            .build()
+    }
+
+    fn tagged(uuid: &str, tags: &[&str]) -> Task {
+        TaskBuilder::new(uuid)
+            .with_estimate(Duration::hours(1))
+            .with(serde_json::json!({"tags": tags}))
+            .build()
+    }
+
+    fn tracked(tag: &str, hours: i64) -> Interval {
+        let start = DateTime::from_timestamp(0, 0).unwrap();
+
+        Interval {
+            start,
+            end: Some(start + Duration::hours(hours)),
+            tags: vec![tag.into()],
+        }
+    }
+
+    #[test]
+    fn subprojects_count_towards_parents() {
+        let tasks = vec![task("a", "work.client", 1), task("b", "work", 1)];
+        let intervals = vec![tracked("a", 2), tracked("b", 1)];
+
+        let accuracy = Accuracy::new(&tasks, &intervals, Utc::now());
+
+        assert_eq!(accuracy.by_project["work"].count, 2);
+        assert_eq!(accuracy.by_project["work.client"].count, 1);
+        assert_eq!(accuracy.overall.ratio(), Some(1.5));
+    }
+
+    #[test]
+    fn corrections_fall_back_to_parent_project() {
+        let tasks: Vec<Task> = (0..3).map(|i| task(&i.to_string(), "work", 1)).collect();
+        let intervals: Vec<Interval> = (0..3).map(|i| tracked(&i.to_string(), 2)).collect();
+
+        let corrections = Accuracy::new(&tasks, &intervals, Utc::now()).corrections();
+
+        assert_eq!(corrections.factor_for(&task("x", "work.client", 1)), 2.0);
+        assert_eq!(corrections.factor_for(&task("x", "home", 1)), 2.0);
+    }
+
+    #[test]
+    fn corrections_need_enough_samples() {
+        let tasks = vec![task("a", "work", 1)];
+        let intervals = vec![tracked("a", 2)];
+
+        let corrections = Accuracy::new(&tasks, &intervals, Utc::now()).corrections();
+
+        assert_eq!(corrections.factor_for(&tasks[0]), 1.0);
+    }
+
+    #[test]
+    fn corrections_use_tags_after_projects() {
+        // Three `email` tasks took twice as long, four `calls` took three
+        // times as long, and three `work` tasks took half as long.
+        let mut tasks: Vec<Task> = (0..3)
+            .map(|i| tagged(&format!("e{i}"), &["email"]))
+            .collect();
+        tasks.extend((0..4).map(|i| tagged(&format!("c{i}"), &["calls"])));
+        tasks.extend((0..3).map(|i| task(&format!("w{i}"), "work", 2)));
+        let intervals: Vec<Interval> = tasks
+            .iter()
+            .map(|task| match task.tags.iter().next().map(String::as_str) {
+                Some("email") => tracked(&task.uuid, 2),
+                Some(_) => tracked(&task.uuid, 3),
+                None => tracked(&task.uuid, 1),
+            })
+            .collect();
+
+        let corrections = Accuracy::new(&tasks, &intervals, Utc::now()).corrections();
+
+        assert_eq!(corrections.factor_for(&tagged("x", &["email"])), 2.0);
+        assert_eq!(
+            corrections.factor_for(&tagged("x", &["email", "calls"])),
+            3.0,
+            "the tag with more finished tasks wins"
+        );
+
+        let mut in_project = tagged("x", &["email"]);
+        in_project.project = Some("work".into());
+        assert_eq!(corrections.factor_for(&in_project), 0.5);
+    }
+}
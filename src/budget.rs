@@ -0,0 +1,212 @@
+use crate::dates;
+use crate::task::Task;
+use chrono::{DateTime, Datelike, Duration, NaiveDate};
+use chrono_tz::Tz;
+use color_eyre::eyre::{eyre, Context, Result};
+use std::{collections::HashMap, str::FromStr};
+
+/// A limit on how much time tasks matching some rule can take up in a day
+/// or week, like `project:work=2h/day` or `tag:meetings=6h/week`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Budget {
+    pub applies_to: Selector,
+    pub limit: Duration,
+    pub period: Period,
+}
+
+impl Budget {
+    pub fn applies_to(&self, task: &Task) -> bool {
+        match &self.applies_to {
+            Selector::Project(project) => task.in_project(project),
+            Selector::Tag(tag) => task.tags.contains(tag),
+        }
+    }
+}
+
+impl FromStr for Budget {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (selector, rest) = s
+            .split_once('=')
+            .ok_or(eyre!("Expected a budget like `project:work=2h/day`"))?;
+
+        let applies_to = match selector.split_once(':') {
+            Some(("project", project)) => Selector::Project(project.to_owned()),
+            Some(("tag", tag)) => Selector::Tag(tag.to_owned()),
+            _ => {
+                return Err(eyre!(
+                    "Expected `project:NAME` or `tag:NAME`, got `{selector}`"
+                ))
+            }
+        };
+
+        let (limit, period) = rest
+            .split_once('/')
+            .ok_or(eyre!("Expected a limit like `2h/day` or `6h/week`"))?;
+
+        Ok(Self {
+            applies_to,
+            limit: crate::dates::parse_duration(limit).wrap_err("could not parse budget limit")?,
+            period: match period {
+                "day" => Period::Day,
+                "week" => Period::Week,
+                _ => return Err(eyre!("Expected `day` or `week`, got `{period}`")),
+            },
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Selector {
+    Project(String),
+    Tag(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Period {
+    Day,
+    Week,
+}
+
+impl Period {
+    /// The first day of the period containing `when`. Weeks start on Monday.
+    fn starting(&self, when: DateTime<Tz>) -> NaiveDate {
+        let date = when.date_naive();
+
+        match self {
+            Self::Day => date,
+            Self::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+        }
+    }
+
+    /// When the period after the one containing `when` starts.
+    fn next(&self, when: DateTime<Tz>) -> DateTime<Tz> {
+        let length = match self {
+            Self::Day => Duration::days(1),
+            Self::Week => Duration::weeks(1),
+        };
+
+        dates::midnight(&when.timezone(), self.starting(when) + length)
+    }
+}
+
+/// Tracks time allocated against each budget as the scheduler goes.
+#[derive(Debug, Default)]
+pub struct Budgets {
+    rules: Vec<Budget>,
+    spent: HashMap<(usize, NaiveDate), Duration>,
+}
+
+impl Budgets {
+    pub fn new(rules: Vec<Budget>) -> Self {
+        Self {
+            rules,
+            spent: HashMap::new(),
+        }
+    }
+
+    /// How much more time this task can have in the period containing
+    /// `when`, or `None` if no budget applies to it.
+    pub fn remaining_for(&self, task: &Task, when: DateTime<Tz>) -> Option<Duration> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.applies_to(task))
+            .map(|(i, rule)| self.left(i, rule, when))
+            .min()
+    }
+
+    /// When every budget this task has used up starts over, or `None` if it
+    /// hasn't used any up.
+    pub fn renews(&self, task: &Task, when: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        self.rules
+            .iter()
+            .enumerate()
+            .filter(|(i, rule)| rule.applies_to(task) && self.left(*i, rule, when).is_zero())
+            .map(|(_, rule)| rule.period.next(when))
+            .max()
+    }
+
+    fn left(&self, i: usize, rule: &Budget, when: DateTime<Tz>) -> Duration {
+        let spent = self
+            .spent
+            .get(&(i, rule.period.starting(when)))
+            .copied()
+            .unwrap_or_else(Duration::zero);
+
+        Duration::zero().max(rule.limit - spent)
+    }
+
+    pub fn record(&mut self, task: &Task, when: DateTime<Tz>, how_much: Duration) {
+        for (i, rule) in self.rules.iter().enumerate() {
+            if rule.applies_to(task) {
+                *self
+                    .spent
+                    .entry((i, rule.period.starting(when)))
+                    .or_insert_with(Duration::zero) += how_much;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_support::TaskBuilder;
+    use chrono::TimeZone;
+    use chrono_tz::America::Chicago;
+
+    #[test]
+    fn parse_project_budget() {
+        assert_eq!(
+            "project:work=2h/day".parse::<Budget>().unwrap(),
+            Budget {
+                applies_to: Selector::Project("work".into()),
+                limit: Duration::hours(2),
+                period: Period::Day,
+            }
+        )
+    }
+
+    #[test]
+    fn parse_tag_budget() {
+        assert_eq!(
+            "tag:meetings=6h/week".parse::<Budget>().unwrap(),
+            Budget {
+                applies_to: Selector::Tag("meetings".into()),
+                limit: Duration::hours(6),
+                period: Period::Week,
+            }
+        )
+    }
+
+    #[test]
+    fn parse_unknown_period() {
+        assert!("tag:meetings=6h/month".parse::<Budget>().is_err())
+    }
+
+    #[test]
+    fn used_up_budgets_renew_next_period() {
+        let task = TaskBuilder::new("abc").with_project("work").build();
+        let wednesday = Chicago.with_ymd_and_hms(2025, 3, 5, 9, 0, 0).unwrap();
+
+        let mut budgets = Budgets::new(vec![
+            "project:work=1h/day".parse().unwrap(),
+            "project:work=2h/week".parse().unwrap(),
+        ]);
+        assert_eq!(budgets.renews(&task, wednesday), None);
+
+        budgets.record(&task, wednesday, Duration::hours(1));
+        assert_eq!(
+            budgets.renews(&task, wednesday),
+            Some(Chicago.with_ymd_and_hms(2025, 3, 6, 0, 0, 0).unwrap())
+        );
+
+        budgets.record(&task, wednesday, Duration::hours(1));
+        assert_eq!(
+            budgets.renews(&task, wednesday),
+            Some(Chicago.with_ymd_and_hms(2025, 3, 10, 0, 0, 0).unwrap())
+        );
+    }
+}
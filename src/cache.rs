@@ -0,0 +1,178 @@
+use crate::caldotcom::ApiError;
+use crate::scheduler::human_time;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{Result, WrapErr};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::path::PathBuf;
+
+/// Responses from calendars, saved on disk so we can still make a plan when
+/// a calendar can't be reached.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    dir: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    saved: DateTime<Utc>,
+    data: T,
+}
+
+impl Cache {
+    pub fn new(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// A cache in a subdirectory of this one, like one per account.
+    pub fn scoped(&self, name: &str) -> Self {
+        let name: String = name
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+                _ => '_',
+            })
+            .collect();
+
+        Self {
+            dir: self.dir.join(name),
+        }
+    }
+
+    /// Wait for `fetch` and save what it returns under `key`. If it fails,
+    /// use what was saved last time instead, with a warning saying how old
+    /// it is. If nothing was saved, return the original error. A rejected
+    /// token is returned right away too: it won't fix itself, and an old
+    /// copy would hide it until the copy was too stale to be useful.
+    pub async fn or_saved<T, F>(&self, key: &str, fetch: F) -> Result<T>
+    where
+        T: Serialize + DeserializeOwned,
+        F: Future<Output = Result<T>>,
+    {
+        let err = match fetch.await {
+            Ok(data) => {
+                if let Err(err) = self.save(key, &data).await {
+                    tracing::warn!(?err, "could not cache calendar response");
+                }
+
+                return Ok(data);
+            }
+            Err(err) => err,
+        };
+
+        if matches!(err.downcast_ref(), Some(ApiError::Unauthorized { .. })) {
+            return Err(err);
+        }
+
+        match self.load(key).await {
+            Ok(Some(Entry { saved, data })) => {
+                tracing::warn!(
+                    "{err:#}. Using a copy saved {} ago instead, which may be out of date.",
+                    human_time(Utc::now() - saved),
+                );
+
+                Ok(data)
+            }
+            Ok(None) => Err(err),
+            Err(cache_err) => {
+                tracing::debug!(?cache_err, "could not load calendar response from cache");
+                Err(err)
+            }
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+
+    async fn load<T: DeserializeOwned>(&self, key: &str) -> Result<Option<Entry<T>>> {
+        let path = self.path(key);
+
+        match tokio::fs::read(&path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .wrap_err_with(|| format!("could not parse {}", path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).wrap_err_with(|| format!("could not read {}", path.display())),
+        }
+    }
+
+    async fn save<T: Serialize>(&self, key: &str, data: &T) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .wrap_err_with(|| format!("could not create {}", self.dir.display()))?;
+
+        let path = self.path(key);
+        let json = serde_json::to_vec(&Entry {
+            saved: Utc::now(),
+            data,
+        })
+        .wrap_err("could not serialize calendar response")?;
+
+        tokio::fs::write(&path, json)
+            .await
+            .wrap_err_with(|| format!("could not write {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use color_eyre::eyre::eyre;
+
+    fn cache(name: &str) -> Cache {
+        let dir =
+            std::env::temp_dir().join(format!("task-sched-cache-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        Cache::new(dir)
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_the_last_response() {
+        let cache = cache("fallback");
+
+        let fresh = cache.or_saved("key", async { Ok(vec![1, 2, 3]) }).await;
+        let stale: Result<Vec<u8>> = cache.or_saved("key", async { Err(eyre!("offline")) }).await;
+
+        assert_eq!(fresh.unwrap(), vec![1, 2, 3]);
+        assert_eq!(stale.unwrap(), vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn errors_without_a_saved_response() {
+        let cache = cache("empty");
+
+        let result: Result<Vec<u8>> = cache.or_saved("key", async { Err(eyre!("offline")) }).await;
+
+        assert_eq!(result.unwrap_err().to_string(), "offline");
+    }
+
+    #[tokio::test]
+    async fn auth_errors_do_not_fall_back() {
+        let cache = cache("auth");
+
+        cache.or_saved("key", async { Ok(1) }).await.unwrap();
+        let result: Result<u8> = cache
+            .or_saved("key", async {
+                Err(ApiError::Unauthorized { message: None }).wrap_err("could not load calendars")
+            })
+            .await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn accounts_are_separate() {
+        let cache = cache("keys");
+
+        cache.or_saved("a", async { Ok(1) }).await.unwrap();
+        let result: Result<u8> = cache
+            .scoped("work")
+            .or_saved("a", async { Err(eyre!("offline")) })
+            .await;
+
+        assert!(result.is_err());
+    }
+}
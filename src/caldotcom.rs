@@ -1,43 +1,130 @@
-use chrono::{DateTime, Local};
+use crate::calendar::BlockedTime;
+use chrono::DateTime;
+use chrono_tz::Tz;
 use color_eyre::eyre::{Result, WrapErr};
-use reqwest::{Client, Url};
-use serde::Deserialize;
+use reqwest::{header, Client, RequestBuilder, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+/// How many times to try a request before giving up.
+const ATTEMPTS: u32 = 3;
+
+/// The longest we'll wait out a rate limit before trying again. Any longer
+/// and we'd rather plan from the cache (or fail) than hang.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(5);
 
 pub struct CalDotCom {
     client: Client,
+    base_url: Url,
     token: String,
 }
 
 impl CalDotCom {
     pub fn new(token: String) -> Self {
         Self {
-            client: Client::new(),
+            client: Client::builder()
+                .connect_timeout(Duration::from_secs(5))
+                .timeout(Duration::from_secs(20))
+                .build()
+                .expect("client settings should be valid"),
+            base_url: Url::parse("https://api.cal.com/v2/").expect("cal.com's URL should be valid"),
             token,
         }
     }
 
-    #[tracing::instrument(skip(self))]
-    pub async fn calendars(&self) -> Result<Response<Calendars>> {
-        self.client
-            .get("https://api.cal.com/v2/calendars")
-            .header("Authorization", format!("Bearer {}", &self.token))
-            .send()
+    /// Talk to cal.com somewhere other than `https://api.cal.com/v2/`, like
+    /// a test server. The URL should end with a slash.
+    pub fn with_base_url(mut self, base_url: Url) -> Self {
+        self.base_url = base_url;
+
+        self
+    }
+
+    fn url(&self, path: &str) -> Url {
+        self.base_url
+            .join(path)
+            .expect("cal.com paths should be valid")
+    }
+
+    /// Send a request and read the JSON in the response, or turn an
+    /// unsuccessful response into an [`ApiError`].
+    async fn fetch<T: DeserializeOwned>(&self, url: Url, what: &str) -> Result<T> {
+        let request = self
+            .client
+            .get(url)
+            .header(header::AUTHORIZATION, format!("Bearer {}", &self.token));
+
+        let response = self
+            .send(request)
             .await
-            .wrap_err("could not fetch calendars")?
+            .wrap_err_with(|| format!("could not fetch {what}"))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let retry_after = retry_after(&response);
+            let body = response.text().await.unwrap_or_default();
+
+            return Err(ApiError::new(status, retry_after, &body))
+                .wrap_err_with(|| format!("could not fetch {what}"));
+        }
+
+        response
             .json()
             .await
-            .wrap_err("could not load calendars from JSON")
+            .wrap_err_with(|| format!("could not load {what} from JSON"))
     }
 
+    /// Send a request, trying again with a growing pause in between if
+    /// cal.com can't be reached or is having trouble. Rate limits are only
+    /// retried if cal.com says when, and that's soon.
+    async fn send(&self, request: RequestBuilder) -> reqwest::Result<reqwest::Response> {
+        let mut delay = Duration::from_millis(500);
+        let mut attempt = 1;
+
+        loop {
+            let result = request
+                .try_clone()
+                .expect("cal.com requests don't have streaming bodies")
+                .send()
+                .await;
+
+            let wait = match &result {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    retry_after(response).filter(|wait| *wait <= MAX_RATE_LIMIT_WAIT)
+                }
+                Ok(response) => response.status().is_server_error().then_some(delay),
+                Err(err) => (err.is_connect() || err.is_timeout()).then_some(delay),
+            };
+
+            let Some(wait) = wait.filter(|_| attempt < ATTEMPTS) else {
+                return result;
+            };
+
+            tracing::debug!(attempt, ?wait, "retrying cal.com request");
+            tokio::time::sleep(wait).await;
+            delay *= 2;
+            attempt += 1;
+        }
+    }
+
+    #[tracing::instrument(skip(self))]
+    pub async fn calendars(&self) -> Result<Response<Calendars>> {
+        self.fetch(self.url("calendars"), "calendars").await
+    }
+
+    /// Busy times on the given calendars. Get their IDs from
+    /// [`CalDotCom::calendars`].
     #[tracing::instrument(skip(self, calendars))]
     pub async fn busy_times(
         &self,
-        calendars: Calendars,
-        start: DateTime<Local>,
-        end: DateTime<Local>,
+        calendars: &[CalendarId],
+        start: DateTime<Tz>,
+        end: DateTime<Tz>,
     ) -> Result<Response<Vec<BlockedTime>>> {
-        let mut params: Vec<(String, String)> = Vec::with_capacity(7);
-        params.push(("loggedInUsersTz".into(), "America/Chicago".into()));
+        let mut params: Vec<(String, String)> = Vec::with_capacity(3 + calendars.len() * 2);
+        params.push(("loggedInUsersTz".into(), start.timezone().name().into()));
         params.push((
             "dateFrom".into(),
             start.date_naive().format("%Y-%m-%d").to_string(),
@@ -47,32 +134,120 @@ impl CalDotCom {
             end.date_naive().format("%Y-%m-%d").to_string(),
         ));
 
-        let mut calendars_to_fetch = Vec::with_capacity(2);
-        for connection in calendars.connected_calendars {
-            for calendar in connection.calendars {
-                if calendar.is_selected {
-                    calendars_to_fetch
-                        .push((connection.credential_id.to_string(), calendar.external_id));
+        for (i, calendar) in calendars.iter().enumerate() {
+            params.push((
+                format!("calendarsToLoad[{i}][credentialId]"),
+                calendar.credential_id.to_string(),
+            ));
+            params.push((
+                format!("calendarsToLoad[{i}][externalId]"),
+                calendar.external_id.clone(),
+            ))
+        }
+
+        let mut url = self.url("calendars/busy-times");
+        url.query_pairs_mut().extend_pairs(params);
+
+        self.fetch(url, "busy-times").await
+    }
+}
+
+/// Why cal.com didn't give us what we asked for.
+#[derive(Debug, PartialEq, Eq)]
+pub enum ApiError {
+    /// The token is wrong, expired, or revoked.
+    Unauthorized { message: Option<String> },
+
+    /// We've made too many requests lately.
+    RateLimited { retry_after: Option<Duration> },
+
+    /// Something went wrong on cal.com's side.
+    Server {
+        status: StatusCode,
+        message: Option<String>,
+    },
+
+    /// cal.com didn't like the request for some other reason.
+    Rejected {
+        status: StatusCode,
+        message: Option<String>,
+    },
+}
+
+impl ApiError {
+    fn new(status: StatusCode, retry_after: Option<Duration>, body: &str) -> Self {
+        let message = serde_json::from_str::<ErrorBody>(body)
+            .ok()
+            .and_then(ErrorBody::message);
+
+        match status {
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Self::Unauthorized { message },
+            StatusCode::TOO_MANY_REQUESTS => Self::RateLimited { retry_after },
+            _ if status.is_server_error() => Self::Server { status, message },
+            _ => Self::Rejected { status, message },
+        }
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unauthorized { message } => {
+                write!(f, "cal.com didn't accept the API token")?;
+                if let Some(message) = message {
+                    write!(f, " ({message})")?;
                 }
+                write!(f, ". It may have expired; check it in cal.com's settings.")
+            }
+            Self::RateLimited {
+                retry_after: Some(retry_after),
+            } => write!(
+                f,
+                "cal.com is rate limiting us. Try again in {} seconds.",
+                retry_after.as_secs()
+            ),
+            Self::RateLimited { retry_after: None } => {
+                write!(f, "cal.com is rate limiting us. Try again in a bit.")
+            }
+            Self::Server { status, message } => {
+                write!(f, "cal.com is having trouble ({status}")?;
+                if let Some(message) = message {
+                    write!(f, ": {message}")?;
+                }
+                write!(f, "). Try again later.")
+            }
+            Self::Rejected { status, message } => {
+                write!(f, "cal.com rejected the request ({status}")?;
+                if let Some(message) = message {
+                    write!(f, ": {message}")?;
+                }
+                write!(f, ")")
             }
         }
-        for (i, (cred, external_id)) in calendars_to_fetch.drain(..).enumerate() {
-            params.push((format!("calendarsToLoad[{i}][credentialId]"), cred));
-            params.push((format!("calendarsToLoad[{i}][externalId]"), external_id))
-        }
+    }
+}
 
-        let url = Url::parse_with_params("https://api.cal.com/v2/calendars/busy-times", params)
-            .wrap_err("could not construct busy-times URL")?;
+impl std::error::Error for ApiError {}
 
-        self.client
-            .get(url)
-            .header("Authorization", format!("Bearer {}", &self.token))
-            .send()
-            .await
-            .wrap_err("could not fetch busy-times")?
-            .json()
-            .await
-            .wrap_err("could not load busy-times from JSON")
+/// What cal.com says went wrong. The v2 API nests it under `error`, but
+/// some errors just have a top-level `message`.
+#[derive(Debug, Deserialize)]
+struct ErrorBody {
+    error: Option<ErrorDetails>,
+    message: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorDetails {
+    message: Option<String>,
+}
+
+impl ErrorBody {
+    fn message(self) -> Option<String> {
+        self.error
+            .and_then(|error| error.message)
+            .or(self.message)
+            .filter(|message| !message.is_empty())
     }
 }
 
@@ -81,20 +256,37 @@ pub struct Response<T> {
     pub data: T,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Calendars {
     #[serde(alias = "connectedCalendars")]
     pub connected_calendars: Vec<CalendarConnection>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct CalendarConnection {
     #[serde(alias = "credentialId")]
     pub credential_id: usize,
     pub calendars: Vec<ConnectedCalendar>,
 }
 
-#[derive(Debug, Deserialize)]
+impl Calendars {
+    /// Every calendar in the account, and whether it's selected in cal.com.
+    pub fn all(&self) -> impl Iterator<Item = (CalendarId, bool)> + '_ {
+        self.connected_calendars.iter().flat_map(|connection| {
+            connection.calendars.iter().map(|calendar| {
+                (
+                    CalendarId {
+                        credential_id: connection.credential_id,
+                        external_id: calendar.external_id.clone(),
+                    },
+                    calendar.is_selected,
+                )
+            })
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct ConnectedCalendar {
     #[serde(alias = "externalId")]
     pub external_id: String,
@@ -102,8 +294,60 @@ pub struct ConnectedCalendar {
     pub is_selected: bool,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct BlockedTime {
-    pub start: DateTime<Local>,
-    pub end: DateTime<Local>,
+/// How long cal.com wants us to wait before trying again, if it said. Only
+/// the number-of-seconds form is understood.
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok()?.parse().ok())
+        .map(Duration::from_secs)
+}
+
+/// Which calendar to load busy times from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CalendarId {
+    pub credential_id: usize,
+    pub external_id: String,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn nested_error_message() {
+        let body = r#"{"status":"error","error":{"code":"UnauthorizedException","message":"Invalid API Key"}}"#;
+
+        assert_eq!(
+            ApiError::new(StatusCode::UNAUTHORIZED, None, body),
+            ApiError::Unauthorized {
+                message: Some("Invalid API Key".into())
+            }
+        );
+    }
+
+    #[test]
+    fn top_level_error_message() {
+        let body = r#"{"message":"dateTo must be after dateFrom"}"#;
+
+        assert_eq!(
+            ApiError::new(StatusCode::BAD_REQUEST, None, body),
+            ApiError::Rejected {
+                status: StatusCode::BAD_REQUEST,
+                message: Some("dateTo must be after dateFrom".into())
+            }
+        );
+    }
+
+    #[test]
+    fn error_without_json() {
+        assert_eq!(
+            ApiError::new(StatusCode::BAD_GATEWAY, None, "<html>Bad Gateway</html>"),
+            ApiError::Server {
+                status: StatusCode::BAD_GATEWAY,
+                message: None
+            }
+        );
+    }
 }
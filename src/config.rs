@@ -1,10 +1,12 @@
 use color_eyre::eyre::{eyre, Context, Result};
+use std::path::PathBuf;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Config {
     pub urgency_due_coefficient: f64,
     pub urgency_age_coefficient: f64,
     pub urgency_age_max: f64,
+    pub data_location: Option<PathBuf>,
 }
 
 impl Config {
@@ -13,6 +15,7 @@ impl Config {
             urgency_age_coefficient: 1.0,
             urgency_age_max: 365.0,
             urgency_due_coefficient: 1.0,
+            data_location: None,
         }
     }
 
@@ -41,6 +44,8 @@ impl Config {
                         v.parse().wrap_err("could not parse due coefficient")?
                 }
 
+                "data.location" => out.data_location = Some(expand_home(v)),
+
                 _ => continue,
             }
         }
@@ -49,6 +54,14 @@ impl Config {
     }
 }
 
+/// Taskwarrior allows `~` at the start of paths in its config.
+fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), std::env::var_os("HOME")) {
+        (Some(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => PathBuf::from(path),
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self::new()
@@ -84,4 +97,11 @@ mod test {
 
         assert_eq!(config.urgency_due_coefficient, 2.5);
     }
+
+    #[test]
+    fn parse_data_location() {
+        let config = Config::parse("data.location=/var/tasks").unwrap();
+
+        assert_eq!(config.data_location, Some(PathBuf::from("/var/tasks")));
+    }
 }
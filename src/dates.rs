@@ -1,6 +1,14 @@
-use chrono::{DateTime, Duration, NaiveDateTime, Utc};
-use serde::de::{self, Visitor};
-use std::fmt;
+use chrono::{
+    DateTime, Duration, FixedOffset, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, Offset,
+    TimeZone, Utc, Weekday,
+};
+use chrono_tz::Tz;
+use color_eyre::eyre::{eyre, Result};
+use serde::{
+    de::{self, Visitor},
+    Deserialize,
+};
+use std::{collections::HashSet, fmt};
 
 struct TWDateTimeVisitor;
 
@@ -22,6 +30,14 @@ impl Visitor<'_> for TWDateTimeVisitor {
     }
 }
 
+/// An RFC 3339 timestamp with any offset, in UTC.
+pub fn any_offset<'de, D>(d: D) -> Result<DateTime<Tz>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    DateTime::<FixedOffset>::deserialize(d).map(|time| time.with_timezone(&Tz::UTC))
+}
+
 pub fn tw_datetime<'de, D>(d: D) -> Result<DateTime<Utc>, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -87,3 +103,198 @@ where
 {
     d.deserialize_str(DurationVisitor)
 }
+
+struct TimeOfDayVisitor;
+
+impl Visitor<'_> for TimeOfDayVisitor {
+    type Value = Option<NaiveTime>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a time of day in the format HH:MM")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        NaiveTime::parse_from_str(value.trim(), "%H:%M")
+            .map(Some)
+            .map_err(de::Error::custom)
+    }
+}
+
+pub fn time_of_day<'de, D>(d: D) -> Result<Option<NaiveTime>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    d.deserialize_str(TimeOfDayVisitor)
+}
+
+struct WeekdaysVisitor;
+
+impl Visitor<'_> for WeekdaysVisitor {
+    type Value = HashSet<Weekday>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a comma-separated list of weekdays, like `tue,thu`")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|day| !day.is_empty())
+            .map(|day| {
+                day.parse()
+                    .map_err(|_| de::Error::custom(format!("`{day}` is not a weekday")))
+            })
+            .collect()
+    }
+}
+
+pub fn weekdays<'de, D>(d: D) -> Result<HashSet<Weekday>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    d.deserialize_str(WeekdaysVisitor)
+}
+
+/// `time` on `date` in `tz`. Daylight saving changes mean some local times
+/// happen twice (we take the first) and some never happen at all (we move
+/// forward by the length of the gap, so 2:30 becomes 3:30 when clocks jump
+/// from 2:00 to 3:00.)
+pub fn at_local(tz: &Tz, date: NaiveDate, time: NaiveTime) -> DateTime<Tz> {
+    let local = date.and_time(time);
+
+    match tz.from_local_datetime(&local) {
+        LocalResult::Single(when) => when,
+        LocalResult::Ambiguous(earliest, _) => earliest,
+        LocalResult::None => {
+            // A day earlier is safely before the gap, so its offset is the
+            // one clocks were using when they jumped.
+            let before = tz
+                .offset_from_utc_datetime(&(local - Duration::days(1)))
+                .fix();
+
+            tz.from_utc_datetime(&(local - Duration::seconds(before.local_minus_utc().into())))
+        }
+    }
+}
+
+/// The start of `date` in `tz`, which isn't always 00:00.
+pub fn midnight(tz: &Tz, date: NaiveDate) -> DateTime<Tz> {
+    at_local(tz, date, NaiveTime::MIN)
+}
+
+/// Parse a duration from the command line. We accept the same ISO8601
+/// durations as the `estimate` UDA (`PT1H30M`) but also shorter ones that are
+/// easier to type, like `90m` or `1h30m`.
+pub fn parse_duration(input: &str) -> Result<Duration> {
+    let input = input.trim();
+
+    if input.starts_with('P') {
+        return iso8601_duration::Duration::parse(input)
+            .map_err(|error| eyre!("could not parse `{input}` as a duration: {error:?}"))?
+            .to_chrono()
+            .ok_or(eyre!("`{input}` is out of range"));
+    }
+
+    let mut out = Duration::zero();
+    let mut digits = String::new();
+
+    for c in input.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+
+        let amount: i64 = digits
+            .parse()
+            .map_err(|_| eyre!("expected a number before `{c}` in `{input}`"))?;
+        digits.clear();
+
+        out += match c {
+            'd' => Duration::days(amount),
+            'h' => Duration::hours(amount),
+            'm' => Duration::minutes(amount),
+            's' => Duration::seconds(amount),
+            _ => return Err(eyre!("unknown unit `{c}` in `{input}` (use d, h, m, or s)")),
+        }
+    }
+
+    if !digits.is_empty() || input.is_empty() {
+        return Err(eyre!("`{input}` needs a unit, like `30m` or `2h`"));
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_minutes() {
+        assert_eq!(parse_duration("90m").unwrap(), Duration::minutes(90))
+    }
+
+    #[test]
+    fn parse_hours_and_minutes() {
+        assert_eq!(parse_duration("1h30m").unwrap(), Duration::minutes(90))
+    }
+
+    #[test]
+    fn parse_seconds() {
+        assert_eq!(parse_duration("30s").unwrap(), Duration::seconds(30))
+    }
+
+    #[test]
+    fn parse_iso8601() {
+        assert_eq!(parse_duration("PT2H").unwrap(), Duration::hours(2))
+    }
+
+    #[test]
+    fn parse_without_unit() {
+        assert!(parse_duration("30").is_err())
+    }
+
+    #[test]
+    fn skipped_local_time_moves_forward() {
+        // Clocks in Chicago went from 2:00 to 3:00 on March 9, 2025.
+        let tz = chrono_tz::America::Chicago;
+        let date = NaiveDate::from_ymd_opt(2025, 3, 9).unwrap();
+
+        assert_eq!(
+            at_local(&tz, date, NaiveTime::from_hms_opt(2, 30, 0).unwrap()),
+            tz.with_ymd_and_hms(2025, 3, 9, 3, 30, 0).unwrap(),
+        )
+    }
+
+    #[test]
+    fn repeated_local_time_is_the_first_one() {
+        // ...and from 2:00 back to 1:00 on November 2.
+        let tz = chrono_tz::America::Chicago;
+        let date = NaiveDate::from_ymd_opt(2025, 11, 2).unwrap();
+        let when = at_local(&tz, date, NaiveTime::from_hms_opt(1, 30, 0).unwrap());
+
+        assert_eq!(
+            when.to_utc(),
+            Utc.with_ymd_and_hms(2025, 11, 2, 6, 30, 0).unwrap()
+        )
+    }
+
+    #[test]
+    fn midnight_when_midnight_is_skipped() {
+        // Santiago skipped from 00:00 to 01:00 on September 8, 2024.
+        let tz = chrono_tz::America::Santiago;
+        let date = NaiveDate::from_ymd_opt(2024, 9, 8).unwrap();
+
+        assert_eq!(
+            midnight(&tz, date),
+            tz.with_ymd_and_hms(2024, 9, 8, 1, 0, 0).unwrap(),
+        )
+    }
+}
@@ -0,0 +1,284 @@
+use crate::scheduler::{human_time, Event, EventData};
+use chrono::{DateTime, Duration};
+use chrono_tz::Tz;
+use color_eyre::eyre::{Context, Result};
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+/// Where a task landed in a schedule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Placement {
+    pub start: DateTime<Tz>,
+    pub duration: Duration,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Change {
+    Added(Placement),
+    Removed(Placement),
+    Moved { before: Placement, after: Placement },
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TaskChange {
+    pub uuid: String,
+    pub name: String,
+    pub change: Change,
+}
+
+/// Compare the tasks in two schedules. Anything in `old` that's already in
+/// the past as of `now` is ignored, so that time passing doesn't look like
+/// every task moved.
+pub fn diff(old: &[Event], new: &[Event], now: DateTime<Tz>) -> Vec<TaskChange> {
+    let old = placements(old, now);
+    let new = placements(new, now);
+
+    let mut out = Vec::new();
+
+    for (uuid, (name, before)) in &old {
+        match new.get(uuid) {
+            None => out.push(TaskChange {
+                uuid: uuid.clone(),
+                name: name.clone(),
+                change: Change::Removed(*before),
+            }),
+            Some((name, after)) if after != before => out.push(TaskChange {
+                uuid: uuid.clone(),
+                name: name.clone(),
+                change: Change::Moved {
+                    before: *before,
+                    after: *after,
+                },
+            }),
+            Some(_) => (),
+        }
+    }
+
+    for (uuid, (name, after)) in &new {
+        if !old.contains_key(uuid) {
+            out.push(TaskChange {
+                uuid: uuid.clone(),
+                name: name.clone(),
+                change: Change::Added(*after),
+            })
+        }
+    }
+
+    out.sort_by_key(|change| match change.change {
+        Change::Added(placement) | Change::Removed(placement) => placement.start,
+        Change::Moved { after, .. } => after.start,
+    });
+
+    out
+}
+
+/// The first start and total duration of each task in a schedule.
+fn placements(events: &[Event], now: DateTime<Tz>) -> BTreeMap<String, (String, Placement)> {
+    let mut out: BTreeMap<String, (String, Placement)> = BTreeMap::new();
+
+    for event in events.iter().filter(|event| event.end > now) {
+        let EventData::Task { uuid, name, .. } = &event.what else {
+            continue;
+        };
+
+        let start = event.start.max(now);
+        let duration = event.end - start;
+
+        out.entry(uuid.clone())
+            .and_modify(|(_, placement)| {
+                placement.start = placement.start.min(start);
+                placement.duration += duration;
+            })
+            .or_insert_with(|| (name.clone(), Placement { start, duration }));
+    }
+
+    out
+}
+
+impl Display for TaskChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.change {
+            Change::Added(after) => {
+                write!(f, "+ {} ({}", when(after.start), human_time(after.duration))?
+            }
+            Change::Removed(before) => write!(
+                f,
+                "- was {} ({}",
+                when(before.start),
+                human_time(before.duration)
+            )?,
+            Change::Moved { before, after } => {
+                if after.start < before.start {
+                    write!(
+                        f,
+                        "< {} ({} earlier",
+                        when(after.start),
+                        human_time(before.start - after.start)
+                    )?
+                } else if after.start > before.start {
+                    write!(
+                        f,
+                        "> {} ({} later",
+                        when(after.start),
+                        human_time(after.start - before.start)
+                    )?
+                } else {
+                    write!(f, "~ {} (", when(after.start))?
+                }
+
+                if after.duration != before.duration {
+                    if after.start != before.start {
+                        f.write_str(", ")?;
+                    }
+                    write!(
+                        f,
+                        "{} instead of {}",
+                        human_time(after.duration),
+                        human_time(before.duration)
+                    )?
+                }
+            }
+        }
+
+        write!(f, ") - {}", self.name)
+    }
+}
+
+fn when(time: DateTime<Tz>) -> impl Display {
+    time.format("%b %-d, %_I:%M %P")
+}
+
+/// The last schedule we generated, saved as JSON.
+pub struct Saved {
+    path: PathBuf,
+}
+
+impl Saved {
+    pub fn new(state_dir: &Path) -> Self {
+        Self {
+            path: state_dir.join("last-schedule.json"),
+        }
+    }
+
+    /// Load the last schedule, with times in `tz`.
+    pub async fn load(&self, tz: &Tz) -> Result<Option<Vec<Event>>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(bytes) => {
+                let mut events: Vec<Event> = serde_json::from_slice(&bytes)
+                    .wrap_err_with(|| format!("could not parse {}", self.path.display()))?;
+
+                for event in &mut events {
+                    event.start = event.start.with_timezone(tz);
+                    event.end = event.end.with_timezone(tz);
+                }
+
+                Ok(Some(events))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => {
+                Err(err).wrap_err_with(|| format!("could not read {}", self.path.display()))
+            }
+        }
+    }
+
+    pub async fn save(&self, events: &[Event]) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .wrap_err_with(|| format!("could not create {}", parent.display()))?;
+        }
+
+        let json = serde_json::to_vec(events).wrap_err("could not serialize schedule")?;
+
+        tokio::fs::write(&self.path, json)
+            .await
+            .wrap_err_with(|| format!("could not write {}", self.path.display()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::America::Chicago;
+
+    fn at(hour: u32) -> DateTime<Tz> {
+        Chicago.with_ymd_and_hms(2025, 3, 4, hour, 0, 0).unwrap()
+    }
+
+    fn task(uuid: &str, start: u32, end: u32) -> Event {
+        Event {
+            start: at(start),
+            end: at(end),
+            what: EventData::Task {
+                uuid: uuid.into(),
+                name: uuid.into(),
+                is_meta: false,
+            },
+        }
+    }
+
+    #[test]
+    fn unchanged() {
+        let schedule = vec![task("a", 9, 10)];
+
+        assert_eq!(diff(&schedule, &schedule, at(8)), Vec::new())
+    }
+
+    #[test]
+    fn added_and_removed() {
+        let changes = diff(&[task("a", 9, 10)], &[task("b", 9, 10)], at(8));
+
+        assert_eq!(
+            changes.iter().map(|c| &c.change).collect::<Vec<_>>(),
+            vec![
+                &Change::Removed(Placement {
+                    start: at(9),
+                    duration: Duration::hours(1)
+                }),
+                &Change::Added(Placement {
+                    start: at(9),
+                    duration: Duration::hours(1)
+                }),
+            ]
+        )
+    }
+
+    #[test]
+    fn moved_later() {
+        let changes = diff(&[task("a", 9, 10)], &[task("a", 11, 12)], at(8));
+
+        assert_eq!(
+            changes[0].change,
+            Change::Moved {
+                before: Placement {
+                    start: at(9),
+                    duration: Duration::hours(1)
+                },
+                after: Placement {
+                    start: at(11),
+                    duration: Duration::hours(1)
+                },
+            }
+        )
+    }
+
+    #[test]
+    fn split_tasks_are_combined() {
+        let old = vec![task("a", 9, 11)];
+        let new = vec![task("a", 9, 10), task("a", 11, 12)];
+
+        assert_eq!(diff(&old, &new, at(8)), Vec::new())
+    }
+
+    #[test]
+    fn time_passing_is_not_a_change() {
+        let old = vec![task("a", 9, 11)];
+        let new = vec![task("a", 10, 11)];
+
+        assert_eq!(diff(&old, &new, at(10)), Vec::new())
+    }
+}
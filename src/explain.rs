@@ -0,0 +1,392 @@
+use chrono::Duration;
+use color_eyre::eyre::{eyre, Result};
+use std::fmt::Write;
+use task_sched::scheduler::{human_time, Event, EventData, Exclusion, Scheduler};
+use task_sched::task::Task;
+use task_sched::windows::Fit;
+
+#[derive(Clone, clap::Args)]
+pub struct Explain {
+    /// The task to explain. A unique prefix (like the short UUID Taskwarrior
+    /// shows) is fine.
+    uuid: String,
+
+    /// How many competing tasks to show for each slot.
+    #[clap(long, default_value = "5")]
+    competitors: usize,
+}
+
+impl Explain {
+    pub fn run(&self, scheduler: &Scheduler) -> Result<()> {
+        let uuid = self.find_uuid(scheduler)?;
+
+        let mut out = String::new();
+        self.explain(&mut out, scheduler, &uuid)?;
+        print!("{out}");
+
+        Ok(())
+    }
+
+    fn find_uuid(&self, scheduler: &Scheduler) -> Result<String> {
+        let mut matches: Vec<&str> = scheduler
+            .tasks()
+            .map(|task| task.uuid.as_str())
+            .chain(scheduler.commitments.iter().filter_map(task_uuid))
+            .filter(|uuid| uuid.starts_with(&self.uuid))
+            .collect();
+        matches.sort();
+        matches.dedup();
+
+        match matches.as_slice() {
+            [uuid] => Ok(uuid.to_string()),
+            [] => Err(eyre!(
+                "No pending task matches `{}`. It may be completed, deleted, or filtered out of the export.",
+                self.uuid
+            )),
+            _ => Err(eyre!(
+                "`{}` matches {} tasks. Use more of the UUID.",
+                self.uuid,
+                matches.len()
+            )),
+        }
+    }
+
+    fn explain(&self, out: &mut String, scheduler: &Scheduler, uuid: &str) -> std::fmt::Result {
+        let slots: Vec<&Event> = scheduler
+            .commitments
+            .iter()
+            .filter(|event| task_uuid(event) == Some(uuid))
+            .collect();
+
+        let tz = scheduler.start().timezone();
+        let Some(task) = scheduler
+            .task(uuid)
+            .filter(|task| task.fixed_start(&tz).is_none())
+        else {
+            // Fixed tasks never go through the scheduler's choices, so the
+            // only thing to say about them is where they were pinned.
+            writeln!(out, "{uuid} is a fixed task.")?;
+            for slot in slots {
+                writeln!(out, "  pinned at {slot}")?;
+            }
+            return Ok(());
+        };
+
+        writeln!(out, "{} ({})\n", task.description, task.uuid)?;
+
+        let written = task.estimate.unwrap_or(Duration::minutes(10));
+        let estimate = scheduler.planned_time(uuid).unwrap_or(written);
+        let scheduled = slots
+            .iter()
+            .fold(Duration::zero(), |total, slot| total + slot.duration());
+        write!(out, "Estimate {}", human_time(estimate))?;
+        if estimate != written {
+            write!(
+                out,
+                " ({} before corrections and time spent)",
+                human_time(written)
+            )?;
+        }
+        writeln!(
+            out,
+            ", scheduled {} in {} slot(s).",
+            human_time(scheduled),
+            slots.len(),
+        )?;
+        for slot in &slots {
+            writeln!(out, "  {slot}")?;
+        }
+
+        let decisions = scheduler.decisions();
+
+        if let Some(remaining) = scheduler
+            .remaining_time(uuid)
+            .filter(|remaining| *remaining > Duration::zero())
+        {
+            // The last time the scheduler looked at this task says why it
+            // stopped getting time. If it was up against other tasks then,
+            // they just used up the rest of the schedule.
+            let last_excluded = decisions
+                .iter()
+                .rev()
+                .find_map(|decision| {
+                    if decision.candidates.iter().any(|c| c.uuid == uuid) {
+                        Some(None)
+                    } else {
+                        decision
+                            .excluded
+                            .iter()
+                            .find(|(id, _)| id == uuid)
+                            .map(|(_, exclusion)| Some(exclusion))
+                    }
+                })
+                .flatten();
+
+            match last_excluded {
+                Some(exclusion) => writeln!(
+                    out,
+                    "\n{} didn't fit: it was {} at the end of the schedule.",
+                    human_time(remaining),
+                    reason(scheduler, exclusion),
+                )?,
+                None => writeln!(
+                    out,
+                    "\n{} didn't fit: there was no time left before the end of the schedule.",
+                    human_time(remaining)
+                )?,
+            }
+        }
+
+        match decisions.iter().find(|d| d.chosen.as_deref() == Some(uuid)) {
+            Some(decision) => {
+                writeln!(
+                    out,
+                    "\nFirst picked at {}:",
+                    decision.at.format("%b %-d, %-I:%M %P")
+                )?;
+                self.write_urgency(out, scheduler, task, decision.at.to_utc())?;
+
+                let mut candidates: Vec<_> = decision.candidates.iter().collect();
+                candidates.sort_by(|a, b| b.fit.cmp(&a.fit).then(b.urgency.total_cmp(&a.urgency)));
+
+                writeln!(out, "\nCompeting in that slot:")?;
+                for candidate in candidates.iter().take(self.competitors + 1) {
+                    write!(
+                        out,
+                        "  {} {:>6.2}  {}",
+                        if candidate.uuid == uuid { ">" } else { " " },
+                        candidate.urgency,
+                        describe(scheduler, &candidate.uuid),
+                    )?;
+                    match candidate.fit {
+                        Fit::InWindow => writeln!(out, " (in its window)")?,
+                        Fit::OutOfWindow => writeln!(out, " (outside its window)")?,
+                        Fit::Neutral => writeln!(out)?,
+                    }
+                }
+            }
+            None => {
+                writeln!(out, "\nNever picked.")?;
+            }
+        }
+
+        // Group up the reasons we couldn't pick this task so that we say
+        // "waiting until Tuesday" once instead of once for every slot.
+        let mut exclusions: Vec<(&Exclusion, usize)> = Vec::new();
+        for decision in decisions {
+            for (_, exclusion) in decision.excluded.iter().filter(|(id, _)| id == uuid) {
+                match exclusions.iter_mut().find(|(e, _)| *e == exclusion) {
+                    Some((_, count)) => *count += 1,
+                    None => exclusions.push((exclusion, 1)),
+                }
+            }
+        }
+
+        let lost = decisions
+            .iter()
+            .filter(|d| d.chosen.as_deref() != Some(uuid))
+            .filter(|d| d.candidates.iter().any(|c| c.uuid == uuid))
+            .count();
+
+        if !exclusions.is_empty() || lost > 0 {
+            writeln!(out, "\nPassed over:")?;
+        }
+        for (exclusion, count) in exclusions {
+            writeln!(out, "  {count} slot(s): {}", reason(scheduler, exclusion))?;
+        }
+        if lost > 0 {
+            writeln!(out, "  {lost} slot(s): a more urgent task won")?;
+        }
+
+        Ok(())
+    }
+
+    fn write_urgency(
+        &self,
+        out: &mut String,
+        scheduler: &Scheduler,
+        task: &Task,
+        when: chrono::DateTime<chrono::Utc>,
+    ) -> std::fmt::Result {
+        let urgency = task.urgency_breakdown_at(when, scheduler.tw_config());
+
+        writeln!(out, "  base           {:>6.2}", urgency.base)?;
+        writeln!(
+            out,
+            "  due/target     {:>6.2} = {:.2} x {:.2}",
+            urgency.due * urgency.due_coefficient,
+            urgency.due,
+            urgency.due_coefficient,
+        )?;
+        writeln!(
+            out,
+            "  age            {:>6.2} = {:.2} x {:.2}",
+            urgency.age * urgency.age_coefficient,
+            urgency.age,
+            urgency.age_coefficient,
+        )?;
+        writeln!(out, "  total          {:>6.2}", urgency.total())
+    }
+}
+
+fn task_uuid(event: &Event) -> Option<&str> {
+    match &event.what {
+        EventData::Task { uuid, .. } => Some(uuid),
+        _ => None,
+    }
+}
+
+/// Like the exclusion's own description, but with blockers' descriptions
+/// instead of their UUIDs.
+fn reason(scheduler: &Scheduler, exclusion: &Exclusion) -> String {
+    match exclusion {
+        Exclusion::BlockedBy(uuids) => {
+            let blockers: Vec<String> =
+                uuids.iter().map(|uuid| describe(scheduler, uuid)).collect();
+            format!("blocked by {}", blockers.join(", "))
+        }
+        _ => exclusion.to_string(),
+    }
+}
+
+fn describe(scheduler: &Scheduler, uuid: &str) -> String {
+    scheduler
+        .task(uuid)
+        .map(|task| task.description.clone())
+        .unwrap_or_else(|| uuid.to_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::America::Chicago;
+    use task_sched::plan::{plan, Inputs};
+    use task_sched::test_support::TaskBuilder;
+
+    fn inputs(tasks: Vec<Task>) -> Inputs {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        Inputs::new(start, start + Duration::hours(8))
+            .with_decision_log()
+            .with_tasks(tasks)
+    }
+
+    fn explain(tasks: Vec<Task>, uuid: &str) -> String {
+        explain_inputs(inputs(tasks), uuid)
+    }
+
+    fn explain_inputs(inputs: Inputs, uuid: &str) -> String {
+        let scheduler = plan(inputs);
+
+        let mut out = String::new();
+        Explain {
+            uuid: uuid.to_string(),
+            competitors: 5,
+        }
+        .explain(&mut out, &scheduler, uuid)
+        .unwrap();
+
+        out
+    }
+
+    #[test]
+    fn scheduled_tasks_say_when_they_were_picked() {
+        let out = explain(
+            vec![TaskBuilder::new("report")
+                .with_estimate(Duration::hours(1))
+                .build()],
+            "report",
+        );
+
+        assert!(out.contains("scheduled 1h in 1 slot(s)"), "{out}");
+        assert!(out.contains("First picked at Mar 3, 9:00 am"), "{out}");
+        assert!(!out.contains("didn't fit"), "{out}");
+    }
+
+    #[test]
+    fn estimates_are_the_ones_the_scheduler_used() {
+        let out = explain_inputs(
+            inputs(vec![TaskBuilder::new("report")
+                .with_estimate(Duration::hours(1))
+                .build()])
+            .with_spent_time(
+                [("report".to_string(), Duration::minutes(20))]
+                    .into_iter()
+                    .collect(),
+            ),
+            "report",
+        );
+
+        assert!(
+            out.contains("Estimate 40m (1h before corrections and time spent), scheduled 40m"),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn estimates_only_mention_the_original_when_it_changed() {
+        let out = explain(
+            vec![TaskBuilder::new("report")
+                .with_estimate(Duration::hours(1))
+                .build()],
+            "report",
+        );
+
+        assert!(out.contains("Estimate 1h, scheduled 1h"), "{out}");
+    }
+
+    #[test]
+    fn leftovers_say_why_they_were_left_over() {
+        let out = explain(
+            vec![TaskBuilder::new("call")
+                .with_estimate(Duration::hours(3))
+                .with(serde_json::json!({"notafter": "10:00"}))
+                .build()],
+            "call",
+        );
+
+        assert!(
+            out.contains(
+                "2h didn't fit: it was outside its notbefore/notafter/weekdays at the end of the schedule."
+            ),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn leftovers_that_lost_ran_out_of_time() {
+        let out = explain(
+            vec![
+                TaskBuilder::new("big")
+                    .with_estimate(Duration::hours(6))
+                    .with_urgency(5.0)
+                    .build(),
+                TaskBuilder::new("small")
+                    .with_estimate(Duration::hours(4))
+                    .build(),
+            ],
+            "small",
+        );
+
+        assert!(
+            out.contains("didn't fit: there was no time left before the end of the schedule."),
+            "{out}"
+        );
+        assert!(out.contains("slot(s): a more urgent task won"), "{out}");
+    }
+
+    #[test]
+    fn fixed_tasks_say_where_they_were_pinned() {
+        let out = explain(
+            vec![TaskBuilder::new("standup")
+                .with_estimate(Duration::minutes(15))
+                .with(serde_json::json!({"scheduled": "20250303T160000Z", "tags": ["fixed"]}))
+                .build()],
+            "standup",
+        );
+
+        assert!(out.starts_with("standup is a fixed task."), "{out}");
+        assert!(out.contains("pinned at"), "{out}");
+    }
+}
@@ -0,0 +1,361 @@
+use crate::Cli;
+use chrono::{DateTime, Days, Duration, Utc};
+use chrono_tz::Tz;
+use color_eyre::eyre::{eyre, Result};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::{BTreeMap, HashMap};
+use task_sched::calendar::BlockedTime;
+use task_sched::dates;
+use task_sched::plan::{plan, Inputs};
+use task_sched::task::Task;
+
+#[derive(Clone, clap::Args)]
+pub struct Forecast {
+    /// How many weeks into the future to simulate. Your calendar for the
+    /// coming week is repeated for every week after it.
+    #[clap(long, default_value = "8")]
+    weeks: u32,
+
+    /// How many simulations to run.
+    #[clap(long, default_value = "200")]
+    runs: usize,
+
+    /// For tasks without an `optimistic` UDA, the fastest they could take, as
+    /// a multiple of `estimate`.
+    #[clap(long, default_value = "0.75")]
+    optimistic_factor: f64,
+
+    /// For tasks without a `pessimistic` UDA, the slowest they could take, as
+    /// a multiple of `estimate`.
+    #[clap(long, default_value = "2.0")]
+    pessimistic_factor: f64,
+
+    /// Seed the random number generator to get the same forecast every time.
+    #[clap(long)]
+    seed: Option<u64>,
+}
+
+impl Forecast {
+    pub async fn run(&self, cli: &Cli, start: DateTime<Tz>) -> Result<()> {
+        if self.runs == 0 {
+            return Err(eyre!("Need at least one run to make a forecast"));
+        }
+        if self.weeks == 0 {
+            return Err(eyre!("Need at least one week to make a forecast"));
+        }
+
+        let model_end = dates::midnight(&start.timezone(), start.date_naive() + Days::new(7));
+        let inputs = cli.inputs(start, model_end).await?;
+
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+
+        let completions = self.simulate(&inputs, &mut rng);
+
+        let mut by_project: BTreeMap<&str, Vec<Option<DateTime<Tz>>>> = BTreeMap::new();
+        for task in &inputs.tasks {
+            let (Some(project), Some(runs)) = (&task.project, completions.get(task.uuid.as_str()))
+            else {
+                continue;
+            };
+
+            let project_runs = by_project
+                .entry(project)
+                .or_insert_with(|| vec![Some(start); self.runs]);
+
+            // A project is done when its last task is.
+            for (project_done, task_done) in project_runs.iter_mut().zip(runs) {
+                *project_done = match (*project_done, task_done) {
+                    (Some(a), Some(b)) => Some(a.max(*b)),
+                    _ => None,
+                }
+            }
+        }
+
+        println!(
+            "Forecast over {} weeks from {} runs. Calendar events after the first week are\n\
+             copies of that week's, so plan for anything unusual coming up.\n",
+            self.weeks, self.runs
+        );
+
+        let mut tasks: Vec<(&Task, Percentiles)> = inputs
+            .tasks
+            .iter()
+            .filter_map(|task| {
+                let runs = completions.get(task.uuid.as_str())?;
+                Some((task, Percentiles::new(runs.clone())))
+            })
+            .collect();
+        tasks.sort_by_key(|(task, percentiles)| {
+            (
+                percentiles.p50.is_none(),
+                percentiles.p50,
+                percentiles.p90.is_none(),
+                percentiles.p90,
+                &task.uuid,
+            )
+        });
+
+        println!("{:<14}{:<14}Task", "P50", "P90");
+        for (task, percentiles) in tasks {
+            println!("{percentiles}{}", task.description);
+        }
+
+        if !by_project.is_empty() {
+            println!("\n{:<14}{:<14}Project", "P50", "P90");
+            for (project, runs) in by_project {
+                println!("{}{project}", Percentiles::new(runs));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Plan `inputs` (the coming week) out to `--weeks` weeks, `--runs`
+    /// times, with different task lengths each time. The first week's busy
+    /// times stand in for every later week. Returns when each task was done
+    /// in each run, or `None` if it wasn't. Fixed tasks are appointments, so
+    /// they keep their length and aren't reported.
+    fn simulate<'a>(
+        &self,
+        inputs: &'a Inputs,
+        rng: &mut impl Rng,
+    ) -> HashMap<&'a str, Vec<Option<DateTime<Tz>>>> {
+        let tz = inputs.start.timezone();
+        let end = dates::midnight(
+            &tz,
+            inputs.start.date_naive() + Days::new(7 * u64::from(self.weeks)),
+        );
+
+        let busy: Vec<BlockedTime> = (0..self.weeks)
+            .flat_map(|week| {
+                inputs.busy.iter().map(move |busy_time| BlockedTime {
+                    start: weeks_later(busy_time.start, week, &tz),
+                    end: weeks_later(busy_time.end, week, &tz),
+                    ..busy_time.clone()
+                })
+            })
+            .collect();
+
+        let mut completions: HashMap<&str, Vec<Option<DateTime<Tz>>>> = HashMap::new();
+
+        for run in 0..self.runs {
+            tracing::debug!(run, "simulating");
+
+            let mut run_inputs = inputs.clone();
+            run_inputs.end = end;
+            run_inputs.busy = busy.clone();
+            for task in &mut run_inputs.tasks {
+                if task.fixed_start(&tz).is_none() {
+                    task.estimate = Some(self.sample(task, rng));
+                }
+            }
+
+            let scheduler = plan(run_inputs);
+
+            for task in inputs
+                .tasks
+                .iter()
+                .filter(|task| task.fixed_start(&tz).is_none())
+            {
+                completions
+                    .entry(&task.uuid)
+                    .or_default()
+                    .push(scheduler.completed_at(&task.uuid));
+            }
+        }
+
+        completions
+    }
+
+    /// Pick how long a task will take this time around from a triangular
+    /// distribution: most likely the estimate, but somewhere between the
+    /// optimistic and pessimistic bounds.
+    fn sample(&self, task: &Task, rng: &mut impl Rng) -> Duration {
+        let estimate = task.estimate.unwrap_or(Duration::minutes(10));
+        let minutes = estimate.num_minutes() as f64;
+
+        let low = task
+            .optimistic
+            .map(|d| d.num_minutes() as f64)
+            .unwrap_or(minutes * self.optimistic_factor)
+            .min(minutes);
+        let high = task
+            .pessimistic
+            .map(|d| d.num_minutes() as f64)
+            .unwrap_or(minutes * self.pessimistic_factor)
+            .max(minutes);
+
+        Duration::minutes(triangular(low, minutes, high, rng.gen()).round() as i64)
+    }
+}
+
+/// Map a uniform sample `u` in `[0, 1)` onto a triangular distribution with
+/// the given minimum, mode, and maximum.
+fn triangular(low: f64, mode: f64, high: f64, u: f64) -> f64 {
+    if high <= low {
+        return mode;
+    }
+
+    let split = (mode - low) / (high - low);
+
+    if u < split {
+        low + (u * (high - low) * (mode - low)).sqrt()
+    } else {
+        high - ((1.0 - u) * (high - low) * (high - mode)).sqrt()
+    }
+}
+
+/// Completion dates across all runs. `None` means the work wasn't done by
+/// the end of the forecast.
+struct Percentiles {
+    p50: Option<DateTime<Tz>>,
+    p90: Option<DateTime<Tz>>,
+}
+
+impl Percentiles {
+    fn new(mut runs: Vec<Option<DateTime<Tz>>>) -> Self {
+        // Runs that never finish sort after everything else.
+        runs.sort_by_key(|done| (done.is_none(), *done));
+
+        let at = |fraction: f64| {
+            let index = ((runs.len() as f64 * fraction).ceil() as usize).saturating_sub(1);
+            runs.get(index).copied().flatten()
+        };
+
+        Self {
+            p50: at(0.5),
+            p90: at(0.9),
+        }
+    }
+}
+
+impl std::fmt::Display for Percentiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for percentile in [self.p50, self.p90] {
+            match percentile {
+                Some(date) => write!(f, "{:<14}", date.format("%a %b %d").to_string())?,
+                None => write!(f, "{:<14}", "later")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The same time on the clock `weeks` weeks later, so a 10:00 meeting stays
+/// at 10:00 after daylight saving starts or ends.
+fn weeks_later(when: DateTime<Utc>, weeks: u32, tz: &Tz) -> DateTime<Utc> {
+    let local = when.with_timezone(tz).naive_local();
+
+    dates::at_local(
+        tz,
+        local.date() + Days::new(7 * u64::from(weeks)),
+        local.time(),
+    )
+    .to_utc()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use task_sched::test_support::TaskBuilder;
+
+    #[test]
+    fn triangular_bounds() {
+        assert_eq!(triangular(1.0, 2.0, 4.0, 0.0), 1.0);
+        assert_eq!(triangular(1.0, 2.0, 4.0, 1.0), 4.0);
+    }
+
+    #[test]
+    fn triangular_mode() {
+        // a third of the weight is below the mode here
+        assert!((triangular(1.0, 2.0, 4.0, 1.0 / 3.0) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn triangular_degenerate() {
+        assert_eq!(triangular(2.0, 2.0, 2.0, 0.5), 2.0);
+    }
+
+    #[test]
+    fn percentiles_with_a_fixed_seed() {
+        use chrono::TimeZone;
+        use chrono_tz::America::Chicago;
+
+        let forecast = Forecast {
+            weeks: 1,
+            runs: 200,
+            optimistic_factor: 0.75,
+            pessimistic_factor: 2.0,
+            seed: Some(1),
+        };
+
+        // An 8h task that could take 4h to 16h finishes on Monday (the first
+        // 8h30m) less than half the time, and always by Tuesday.
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+        let inputs = Inputs::new(start, start + Duration::days(7)).with_tasks(vec![
+            TaskBuilder::new("report")
+                .with_estimate(Duration::hours(8))
+                .with_urgency(2.0)
+                .with(serde_json::json!({"optimistic": "PT4H", "pessimistic": "PT16H"}))
+                .build(),
+            TaskBuilder::new("novel")
+                .with_estimate(Duration::hours(200))
+                .build(),
+        ]);
+
+        let completions = forecast.simulate(&inputs, &mut StdRng::seed_from_u64(1));
+        let again = forecast.simulate(&inputs, &mut StdRng::seed_from_u64(1));
+        assert_eq!(completions, again, "the same seed gives the same runs");
+
+        let report = Percentiles::new(completions["report"].clone());
+        assert_eq!(
+            report.to_string(),
+            format!("{:<14}{:<14}", "Tue Mar 04", "Tue Mar 04")
+        );
+        assert!(completions["report"]
+            .iter()
+            .any(|done| done.is_some_and(|done| done.date_naive() == start.date_naive())));
+
+        let novel = Percentiles::new(completions["novel"].clone());
+        assert_eq!(novel.to_string(), format!("{:<14}{:<14}", "later", "later"));
+    }
+
+    #[test]
+    fn fixed_tasks_keep_their_length() {
+        use chrono::TimeZone;
+        use chrono_tz::America::Chicago;
+
+        let forecast = Forecast {
+            weeks: 1,
+            runs: 50,
+            optimistic_factor: 0.75,
+            pessimistic_factor: 2.0,
+            seed: Some(1),
+        };
+
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+        // The rest of Monday exactly fits a task that can't run long, as long
+        // as the appointment stays an hour.
+        let inputs = Inputs::new(start, start + Duration::days(7)).with_tasks(vec![
+            TaskBuilder::new("standup")
+                .with_estimate(Duration::hours(1))
+                .with(serde_json::json!({"scheduled": "20250303T150000Z", "tags": ["fixed"]}))
+                .build(),
+            TaskBuilder::new("exact")
+                .with_estimate(Duration::minutes(7 * 60 + 30))
+                .with(serde_json::json!({"optimistic": "PT7H30M", "pessimistic": "PT7H30M"}))
+                .build(),
+        ]);
+
+        let completions = forecast.simulate(&inputs, &mut StdRng::seed_from_u64(1));
+
+        assert!(!completions.contains_key("standup"));
+        assert!(completions["exact"]
+            .iter()
+            .all(|done| *done == Some(start + Duration::minutes(8 * 60 + 30))));
+    }
+}
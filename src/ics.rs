@@ -0,0 +1,858 @@
+use crate::calendar::{BlockedTime, Status};
+use crate::dates;
+use crate::scheduler::{Event, EventData};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use std::collections::{HashMap, HashSet};
+
+/// Render the scheduled tasks as an iCalendar feed, so calendar apps can show
+/// them alongside meetings. Only tasks are included; everything else already
+/// came from a calendar.
+pub fn render<Z: TimeZone>(events: &[Event], now: DateTime<Z>) -> String {
+    let now = stamp(now);
+    let mut out = String::new();
+
+    line(&mut out, "BEGIN:VCALENDAR");
+    line(&mut out, "VERSION:2.0");
+    line(&mut out, "PRODID:-//task-sched//task-sched//EN");
+    line(&mut out, "CALSCALE:GREGORIAN");
+    line(&mut out, "X-WR-CALNAME:task-sched");
+
+    for event in events {
+        let EventData::Task { uuid, name, .. } = &event.what else {
+            continue;
+        };
+
+        let start = stamp(event.start);
+
+        line(&mut out, "BEGIN:VEVENT");
+        line(&mut out, &format!("UID:{uuid}-{start}@task-sched"));
+        line(&mut out, &format!("DTSTAMP:{now}"));
+        line(&mut out, &format!("DTSTART:{start}"));
+        line(&mut out, &format!("DTEND:{}", stamp(event.end)));
+        line(&mut out, &format!("SUMMARY:{}", escape(name)));
+        line(&mut out, "TRANSP:OPAQUE");
+        line(&mut out, "END:VEVENT");
+    }
+
+    line(&mut out, "END:VCALENDAR");
+
+    out
+}
+
+fn stamp<Z: TimeZone>(time: DateTime<Z>) -> String {
+    time.with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' | ';' | ',' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            '\r' => (),
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
+
+/// Write a content line, folding it so no physical line is longer than 75
+/// octets (RFC 5545 section 3.1.)
+fn line(out: &mut String, content: &str) {
+    let mut width = 0;
+
+    for c in content.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+
+        out.push(c);
+        width += c.len_utf8();
+    }
+
+    out.push_str("\r\n");
+}
+
+/// Busy times for the events in an iCalendar file that overlap `start` to
+/// `end`. Recurring events are expanded (as long as their rules are simple
+/// enough; see [`Rule`]) and cancelled events are left out. Times without a
+/// time zone, or with one we don't recognize, are taken to be in `start`'s.
+pub fn busy_times(text: &str, start: DateTime<Tz>, end: DateTime<Tz>) -> Vec<BlockedTime> {
+    let tz = start.timezone();
+    let events = parse(text, &tz);
+
+    // Changed instances of a recurring event come as their own VEVENTs with
+    // a RECURRENCE-ID, and replace the instance they point to.
+    let mut overridden: HashMap<&str, HashSet<DateTime<Utc>>> = HashMap::new();
+    for event in &events {
+        if let (Some(uid), Some(id)) = (&event.uid, event.recurrence_id) {
+            overridden.entry(uid).or_default().insert(id);
+        }
+    }
+
+    let mut out = Vec::new();
+    for event in &events {
+        let Some(status) = event.status() else {
+            continue;
+        };
+
+        let is_overridden = |at: DateTime<Utc>| {
+            event.recurrence_id.is_none()
+                && event
+                    .uid
+                    .as_deref()
+                    .and_then(|uid| overridden.get(uid))
+                    .is_some_and(|ids| ids.contains(&at))
+        };
+
+        for local in event.occurrences(end.to_utc()) {
+            let (event_start, event_end) = event.span(local);
+
+            if event_end > start.to_utc()
+                && event_start < end.to_utc()
+                && !event.exdates.contains(&event_start)
+                && !is_overridden(event_start)
+            {
+                out.push(BlockedTime {
+                    start: event_start,
+                    end: event_end,
+                    status,
+                    title: event.summary.clone(),
+                });
+            }
+        }
+    }
+
+    out
+}
+
+/// A time as written in the file, and the zone to read it in.
+#[derive(Debug, Clone, Copy)]
+struct Moment {
+    local: NaiveDateTime,
+    zone: Tz,
+    all_day: bool,
+}
+
+impl Moment {
+    /// Read a DTSTART-style value. `tz` is for floating times.
+    fn parse(property: &Property, tz: &Tz) -> Option<Self> {
+        let value = property.value.trim();
+
+        if property.param("VALUE") == Some("DATE") || value.len() == 8 {
+            let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+
+            return Some(Self {
+                local: date.and_time(chrono::NaiveTime::MIN),
+                zone: *tz,
+                all_day: true,
+            });
+        }
+
+        if let Some(utc) = value.strip_suffix('Z') {
+            return Some(Self {
+                local: NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?,
+                zone: Tz::UTC,
+                all_day: false,
+            });
+        }
+
+        Some(Self {
+            local: NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?,
+            zone: property
+                .param("TZID")
+                .map(|tzid| zone(tzid, tz))
+                .unwrap_or(*tz),
+            all_day: false,
+        })
+    }
+
+    /// `local`, read in this moment's zone.
+    fn at(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        dates::at_local(&self.zone, local.date(), local.time()).to_utc()
+    }
+}
+
+/// Most servers use IANA names for TZID, sometimes with a prefix like
+/// `/mozilla.org/20050126_1/America/Chicago`. Anything else (like Windows
+/// zone names) falls back to the time zone we're planning in.
+fn zone(tzid: &str, fallback: &Tz) -> Tz {
+    let candidates =
+        std::iter::once(tzid).chain(tzid.match_indices('/').map(|(i, _)| &tzid[i + 1..]));
+
+    for candidate in candidates {
+        if let Ok(zone) = candidate.parse() {
+            return zone;
+        }
+    }
+
+    tracing::warn!(tzid, "unknown time zone in calendar, using {fallback}");
+    *fallback
+}
+
+#[derive(Debug, Default)]
+struct VEvent {
+    uid: Option<String>,
+    start: Option<Moment>,
+    end: Option<Moment>,
+    duration: Option<Duration>,
+    summary: Option<String>,
+    status: Option<String>,
+    transparency: Option<String>,
+    busy_status: Option<String>,
+    rule: Option<String>,
+    exdates: Vec<DateTime<Utc>>,
+    recurrence_id: Option<DateTime<Utc>>,
+}
+
+impl VEvent {
+    /// What the event means for availability, or `None` if it's cancelled.
+    fn status(&self) -> Option<Status> {
+        if self.status.as_deref() == Some("CANCELLED") {
+            return None;
+        }
+
+        // Outlook and Exchange are more specific than the standard allows.
+        let status = match self.busy_status.as_deref() {
+            Some("FREE") => Status::Free,
+            Some("TENTATIVE") => Status::Tentative,
+            Some("OOF") => Status::OutOfOffice,
+            Some("WORKINGELSEWHERE") => Status::WorkingElsewhere,
+            Some("BUSY") => Status::Busy,
+            _ if self.transparency.as_deref() == Some("TRANSPARENT") => Status::Free,
+            _ if self.status.as_deref() == Some("TENTATIVE") => Status::Tentative,
+            _ => Status::Busy,
+        };
+
+        Some(status)
+    }
+
+    /// Local start times of every occurrence starting before `until`.
+    fn occurrences(&self, until: DateTime<Utc>) -> Vec<NaiveDateTime> {
+        let Some(start) = self.start else {
+            return Vec::new();
+        };
+
+        let Some(rule) = &self.rule else {
+            return vec![start.local];
+        };
+
+        match rule.parse::<Rule>() {
+            Ok(rule) => rule.expand(&start, until),
+            Err(reason) => {
+                tracing::warn!(
+                    summary = self.summary,
+                    rule,
+                    reason,
+                    "can't expand recurring event, so only using its first occurrence",
+                );
+                vec![start.local]
+            }
+        }
+    }
+
+    /// When the occurrence starting at `local` starts and ends.
+    fn span(&self, local: NaiveDateTime) -> (DateTime<Utc>, DateTime<Utc>) {
+        let start = self
+            .start
+            .expect("only events with a start have occurrences");
+        let at = start.at(local);
+
+        // Lengths are kept on the clock rather than in absolute time, so an
+        // all-day event is still all day when the clocks change.
+        let end = match (&self.end, self.duration) {
+            (Some(end), _) => end.at(local + (end.local - start.local)),
+            (None, Some(duration)) if start.all_day => start.at(local + duration),
+            (None, Some(duration)) => at + duration,
+            (None, None) if start.all_day => start.at(local + Duration::days(1)),
+            (None, None) => at,
+        };
+
+        (at, end)
+    }
+}
+
+/// The parts of RFC 5545 recurrence rules we can expand: `FREQ`, `INTERVAL`,
+/// `COUNT`, `UNTIL`, and `BYDAY` with plain weekdays on weekly rules. That
+/// covers what people make in calendar apps day to day.
+#[derive(Debug, PartialEq)]
+struct Rule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<u32>,
+    until: Option<String>,
+    weekdays: Vec<Weekday>,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl std::str::FromStr for Rule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut frequency = None;
+        let mut rule = Self {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            weekdays: Vec::new(),
+        };
+
+        for part in s.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("`{part}` isn't KEY=VALUE"))?;
+
+            match key.to_ascii_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_ascii_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        other => return Err(format!("{other} frequency isn't supported")),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value
+                        .parse()
+                        .ok()
+                        .filter(|interval| *interval > 0)
+                        .ok_or_else(|| format!("bad interval `{value}`"))?
+                }
+                "COUNT" => {
+                    rule.count = Some(value.parse().map_err(|_| format!("bad count `{value}`"))?)
+                }
+                "UNTIL" => rule.until = Some(value.to_owned()),
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        rule.weekdays.push(
+                            weekday(day).ok_or_else(|| format!("`BYDAY={day}` isn't supported"))?,
+                        );
+                    }
+                }
+                "WKST" => (),
+                other => return Err(format!("{other} isn't supported")),
+            }
+        }
+
+        rule.frequency = frequency.ok_or("rules need a FREQ")?;
+        if !rule.weekdays.is_empty() && rule.frequency != Frequency::Weekly {
+            return Err("BYDAY is only supported on weekly rules".into());
+        }
+        rule.weekdays.sort_by_key(|day| day.num_days_from_monday());
+
+        Ok(rule)
+    }
+}
+
+fn weekday(day: &str) -> Option<Weekday> {
+    Some(match day.trim().to_ascii_uppercase().as_str() {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ => return None,
+    })
+}
+
+impl Rule {
+    /// Give up after this many periods, in case of a rule that never ends
+    /// and never produces anything (like the 31st of every other month.)
+    const MAX_PERIODS: u32 = 10_000;
+
+    fn expand(&self, start: &Moment, until: DateTime<Utc>) -> Vec<NaiveDateTime> {
+        // UNTIL is inclusive, and is a date when DTSTART is.
+        let last = self.until.as_deref().and_then(|value| {
+            let property = Property {
+                name: "UNTIL".into(),
+                params: Vec::new(),
+                value: value.into(),
+            };
+            let moment = Moment::parse(&property, &start.zone)?;
+
+            Some(if moment.all_day {
+                moment.at(moment.local + Duration::days(1)) - Duration::seconds(1)
+            } else {
+                moment.at(moment.local)
+            })
+        });
+
+        let mut out = Vec::new();
+        let mut seen = 0;
+
+        for period in 0..Self::MAX_PERIODS {
+            for local in self.period(start.local, period * self.interval) {
+                if local < start.local {
+                    continue;
+                }
+
+                let at = start.at(local);
+                if self.count.is_some_and(|count| seen >= count)
+                    || last.is_some_and(|last| at > last)
+                    || at >= until
+                {
+                    return out;
+                }
+
+                seen += 1;
+                out.push(local);
+            }
+        }
+
+        out
+    }
+
+    /// Candidate start times `offset` periods after `first`.
+    fn period(&self, first: NaiveDateTime, offset: u32) -> Vec<NaiveDateTime> {
+        let time = first.time();
+        let date = first.date();
+
+        match self.frequency {
+            Frequency::Daily => vec![first + Duration::days(offset.into())],
+            Frequency::Weekly if self.weekdays.is_empty() => {
+                vec![first + Duration::weeks(offset.into())]
+            }
+            Frequency::Weekly => {
+                let monday = date - Duration::days(date.weekday().num_days_from_monday().into())
+                    + Duration::weeks(offset.into());
+
+                self.weekdays
+                    .iter()
+                    .map(|day| {
+                        (monday + Duration::days(day.num_days_from_monday().into())).and_time(time)
+                    })
+                    .collect()
+            }
+            // Months (and years, for February 29th) without the day get
+            // skipped rather than moved.
+            Frequency::Monthly | Frequency::Yearly => {
+                let months = if self.frequency == Frequency::Yearly {
+                    offset * 12
+                } else {
+                    offset
+                };
+                let index = date.year() * 12 + date.month0() as i32 + months as i32;
+
+                NaiveDate::from_ymd_opt(
+                    index.div_euclid(12),
+                    index.rem_euclid(12) as u32 + 1,
+                    date.day(),
+                )
+                .map(|date| date.and_time(time))
+                .into_iter()
+                .collect()
+            }
+        }
+    }
+}
+
+/// A content line, like `DTSTART;TZID=America/Chicago:20250304T090000`.
+#[derive(Debug)]
+struct Property {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl Property {
+    fn parse(line: &str) -> Option<Self> {
+        // Parameter values can be quoted, and those can have colons in them.
+        let mut quoted = false;
+        let colon = line.char_indices().find_map(|(i, c)| match c {
+            '"' => {
+                quoted = !quoted;
+                None
+            }
+            ':' if !quoted => Some(i),
+            _ => None,
+        })?;
+
+        let mut head = line[..colon].split(';');
+        let name = head.next()?.trim().to_ascii_uppercase();
+        let params = head
+            .filter_map(|param| param.split_once('='))
+            .map(|(key, value)| {
+                (
+                    key.trim().to_ascii_uppercase(),
+                    value.trim_matches('"').to_owned(),
+                )
+            })
+            .collect();
+
+        Some(Self {
+            name,
+            params,
+            value: line[colon + 1..].to_owned(),
+        })
+    }
+
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Every VEVENT in the file, without expanding anything yet.
+fn parse(text: &str, tz: &Tz) -> Vec<VEvent> {
+    let mut events = Vec::new();
+    let mut current: Option<VEvent> = None;
+
+    // Events can have components inside them (alarms, mostly) whose
+    // properties aren't about the event.
+    let mut nested = 0;
+
+    for line in unfold(text) {
+        let Some(property) = Property::parse(&line) else {
+            continue;
+        };
+        let value = property.value.trim();
+
+        match (property.name.as_str(), current.as_mut()) {
+            ("BEGIN", None) if value.eq_ignore_ascii_case("VEVENT") => {
+                current = Some(VEvent::default())
+            }
+            ("BEGIN", Some(_)) => nested += 1,
+            ("END", Some(_)) if nested > 0 => nested -= 1,
+            ("END", Some(_)) if value.eq_ignore_ascii_case("VEVENT") => {
+                events.extend(current.take())
+            }
+            (_, None) => (),
+            (_, Some(_)) if nested > 0 => (),
+
+            ("UID", Some(event)) => event.uid = Some(value.to_owned()),
+            ("DTSTART", Some(event)) => event.start = Moment::parse(&property, tz),
+            ("DTEND", Some(event)) => event.end = Moment::parse(&property, tz),
+            ("DURATION", Some(event)) => event.duration = dates::parse_duration(value).ok(),
+            ("SUMMARY", Some(event)) => event.summary = Some(unescape(&property.value)),
+            ("STATUS", Some(event)) => event.status = Some(value.to_ascii_uppercase()),
+            ("TRANSP", Some(event)) => event.transparency = Some(value.to_ascii_uppercase()),
+            ("X-MICROSOFT-CDO-BUSYSTATUS", Some(event)) => {
+                event.busy_status = Some(value.to_ascii_uppercase())
+            }
+            ("RRULE", Some(event)) => event.rule = Some(value.to_owned()),
+            ("RECURRENCE-ID", Some(event)) => {
+                event.recurrence_id = Moment::parse(&property, tz).map(|id| id.at(id.local))
+            }
+            ("EXDATE", Some(event)) => {
+                for exdate in value.split(',') {
+                    let one = Property {
+                        name: property.name.clone(),
+                        params: property.params.clone(),
+                        value: exdate.to_owned(),
+                    };
+                    event
+                        .exdates
+                        .extend(Moment::parse(&one, tz).map(|at| at.at(at.local)));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    events
+}
+
+/// Undo line folding: lines starting with a space or tab continue the one
+/// before.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for raw in text.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ if raw.is_empty() => (),
+            _ => lines.push(raw.to_owned()),
+        }
+    }
+
+    lines
+}
+
+fn unescape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n' | 'N') => out.push('\n'),
+            Some(other) => out.push(other),
+            None => (),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono_tz::America::Chicago;
+
+    #[test]
+    fn escapes_text() {
+        assert_eq!(escape("a, b; c\\d\ne"), "a\\, b\\; c\\\\d\\ne")
+    }
+
+    #[test]
+    fn folds_long_lines() {
+        let mut out = String::new();
+        line(&mut out, &"x".repeat(100));
+
+        for physical in out.split("\r\n") {
+            assert!(physical.len() <= 75, "{physical:?} is too long");
+        }
+        assert_eq!(out.replace("\r\n ", ""), format!("{}\r\n", "x".repeat(100)));
+    }
+
+    #[test]
+    fn only_includes_tasks() {
+        let start = Utc.with_ymd_and_hms(2025, 3, 4, 15, 0, 0).unwrap();
+        let events = vec![
+            Event {
+                start: start.with_timezone(&Chicago),
+                end: (start + chrono::Duration::hours(1)).with_timezone(&Chicago),
+                what: EventData::Blocked,
+            },
+            Event {
+                start: start.with_timezone(&Chicago),
+                end: (start + chrono::Duration::hours(1)).with_timezone(&Chicago),
+                what: EventData::Task {
+                    uuid: "abc".into(),
+                    name: "Write report".into(),
+                    is_meta: false,
+                },
+            },
+        ];
+
+        let ics = render(&events, start);
+
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ics.contains("DTSTART:20250304T150000Z\r\n"));
+        assert!(ics.contains("DTEND:20250304T160000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Write report\r\n"));
+    }
+
+    fn week() -> (DateTime<Tz>, DateTime<Tz>) {
+        (
+            Chicago.with_ymd_and_hms(2025, 3, 3, 0, 0, 0).unwrap(),
+            Chicago.with_ymd_and_hms(2025, 3, 10, 0, 0, 0).unwrap(),
+        )
+    }
+
+    fn calendar(events: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{events}END:VCALENDAR\r\n")
+    }
+
+    fn starts(busy: &[BlockedTime]) -> Vec<String> {
+        busy.iter()
+            .map(|time| {
+                time.start
+                    .with_timezone(&Chicago)
+                    .format("%a %H:%M")
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reads_events_in_their_time_zones() {
+        let (start, end) = week();
+        let busy = busy_times(
+            &calendar(concat!(
+                "BEGIN:VEVENT\r\n",
+                "UID:1\r\n",
+                "DTSTART;TZID=America/New_York:20250304T100000\r\n",
+                "DTEND;TZID=America/New_York:20250304T110000\r\n",
+                "SUMMARY:Planning\\, part 2\r\n",
+                "BEGIN:VALARM\r\n",
+                "SUMMARY:Reminder\r\n",
+                "END:VALARM\r\n",
+                "END:VEVENT\r\n",
+                "BEGIN:VEVENT\r\n",
+                "UID:2\r\n",
+                "DTSTART:20250305T150000Z\r\n",
+                "DURATION:PT30M\r\n",
+                "SUMMARY:A long title that goes on and on and on so that it has to be fol\r\n",
+                " ded\r\n",
+                "END:VEVENT\r\n",
+            )),
+            start,
+            end,
+        );
+
+        assert_eq!(starts(&busy), vec!["Tue 09:00", "Wed 09:00"]);
+        assert_eq!(busy[0].end - busy[0].start, Duration::hours(1));
+        assert_eq!(busy[0].title.as_deref(), Some("Planning, part 2"));
+        assert_eq!(busy[1].end - busy[1].start, Duration::minutes(30));
+        assert!(busy[1]
+            .title
+            .as_deref()
+            .unwrap()
+            .ends_with("so that it has to be folded"));
+    }
+
+    #[test]
+    fn all_day_events_follow_the_clock() {
+        let (start, end) = week();
+        let busy = busy_times(
+            &calendar(concat!(
+                "BEGIN:VEVENT\r\n",
+                "DTSTART;VALUE=DATE:20250309\r\n",
+                "DTEND;VALUE=DATE:20250310\r\n",
+                "SUMMARY:Sunday\r\n",
+                "END:VEVENT\r\n",
+            )),
+            start,
+            end,
+        );
+
+        // Daylight saving starts that day, so it's 23 hours long.
+        assert_eq!(
+            busy[0].start,
+            Chicago.with_ymd_and_hms(2025, 3, 9, 0, 0, 0).unwrap()
+        );
+        assert_eq!(busy[0].end - busy[0].start, Duration::hours(23));
+    }
+
+    #[test]
+    fn expands_weekly_rules() {
+        let (start, end) = week();
+        let busy = busy_times(
+            &calendar(concat!(
+                "BEGIN:VEVENT\r\n",
+                "UID:standup\r\n",
+                "DTSTART;TZID=America/Chicago:20250224T093000\r\n",
+                "DTEND;TZID=America/Chicago:20250224T094500\r\n",
+                "RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR\r\n",
+                "EXDATE;TZID=America/Chicago:20250305T093000\r\n",
+                "SUMMARY:Standup\r\n",
+                "END:VEVENT\r\n",
+                "BEGIN:VEVENT\r\n",
+                "UID:standup\r\n",
+                "RECURRENCE-ID;TZID=America/Chicago:20250307T093000\r\n",
+                "DTSTART;TZID=America/Chicago:20250307T110000\r\n",
+                "DTEND;TZID=America/Chicago:20250307T111500\r\n",
+                "SUMMARY:Standup (moved)\r\n",
+                "END:VEVENT\r\n",
+            )),
+            start,
+            end,
+        );
+
+        assert_eq!(starts(&busy), vec!["Mon 09:30", "Fri 11:00"]);
+    }
+
+    #[test]
+    fn stops_at_count_and_until() {
+        let (start, end) = week();
+        let busy = busy_times(
+            &calendar(concat!(
+                "BEGIN:VEVENT\r\n",
+                "DTSTART;TZID=America/Chicago:20250301T080000\r\n",
+                "DTEND;TZID=America/Chicago:20250301T083000\r\n",
+                "RRULE:FREQ=DAILY;COUNT=4\r\n",
+                "END:VEVENT\r\n",
+                "BEGIN:VEVENT\r\n",
+                "DTSTART;TZID=America/Chicago:20250303T160000\r\n",
+                "DTEND;TZID=America/Chicago:20250303T170000\r\n",
+                "RRULE:FREQ=DAILY;INTERVAL=2;UNTIL=20250307T220000Z\r\n",
+                "END:VEVENT\r\n",
+            )),
+            start,
+            end,
+        );
+
+        assert_eq!(
+            starts(&busy),
+            vec![
+                "Mon 08:00",
+                "Tue 08:00",
+                "Mon 16:00",
+                "Wed 16:00",
+                "Fri 16:00"
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_statuses() {
+        let (start, end) = week();
+        let event = |extra: &str| {
+            format!("BEGIN:VEVENT\r\nDTSTART:20250304T150000Z\r\nDTEND:20250304T160000Z\r\n{extra}END:VEVENT\r\n")
+        };
+        let busy = busy_times(
+            &calendar(
+                &[
+                    event(""),
+                    event("STATUS:TENTATIVE\r\n"),
+                    event("TRANSP:TRANSPARENT\r\n"),
+                    event("X-MICROSOFT-CDO-BUSYSTATUS:OOF\r\n"),
+                    event("STATUS:CANCELLED\r\n"),
+                ]
+                .concat(),
+            ),
+            start,
+            end,
+        );
+
+        let statuses: Vec<Status> = busy.iter().map(|time| time.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                Status::Busy,
+                Status::Tentative,
+                Status::Free,
+                Status::OutOfOffice
+            ]
+        );
+    }
+
+    #[test]
+    fn unsupported_rules_keep_the_first_occurrence() {
+        assert!("FREQ=MONTHLY;BYDAY=-1FR".parse::<Rule>().is_err());
+
+        let (start, end) = week();
+        let busy = busy_times(
+            &calendar(concat!(
+                "BEGIN:VEVENT\r\n",
+                "DTSTART:20250304T150000Z\r\n",
+                "DTEND:20250304T160000Z\r\n",
+                "RRULE:FREQ=DAILY;BYHOUR=9,15\r\n",
+                "END:VEVENT\r\n",
+            )),
+            start,
+            end,
+        );
+
+        assert_eq!(starts(&busy), vec!["Tue 09:00"]);
+    }
+}
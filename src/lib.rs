@@ -0,0 +1,88 @@
+//! Schedule Taskwarrior tasks around calendar events.
+//!
+//! The `task-sched` binary is a thin layer over this library. To make a plan
+//! yourself, gather [`Inputs`] (from Taskwarrior and a calendar, or however
+//! you like) and pass them to [`plan()`]:
+//!
+//! ```
+//! use chrono::{Duration, Utc};
+//! use chrono_tz::America::Chicago;
+//! use task_sched::{plan, Inputs};
+//!
+//! let start = Utc::now().with_timezone(&Chicago);
+//! let scheduler = plan(Inputs::new(start, start + Duration::days(3)));
+//!
+//! for event in &scheduler.commitments {
+//!     println!("{event}");
+//! }
+//! ```
+//!
+//! [`Event`] and [`EventData`] serialize to the same JSON the CLI prints, so
+//! they're safe to hand to other programs.
+
+/// Comparing estimates with time actually tracked.
+pub mod accuracy;
+
+/// Limits on how much time a project or tag gets per day or week.
+pub mod budget;
+
+/// Calendar responses saved on disk, for when a calendar can't be reached.
+pub mod cache;
+
+/// Busy times from CalDAV servers, like Fastmail or Nextcloud.
+pub mod caldav;
+
+/// Busy times from cal.com.
+pub mod caldotcom;
+
+/// Busy times from every calendar account, merged together.
+pub mod calendar;
+
+/// The parts of Taskwarrior's configuration we care about.
+pub mod config;
+
+/// Parsing dates, times, and durations in the formats Taskwarrior uses.
+pub mod dates;
+
+/// What changed between two schedules.
+pub mod diff;
+
+/// Reading and writing iCalendar files.
+pub mod ics;
+
+/// Where to keep state between runs.
+pub mod paths;
+
+/// Turning tasks and calendar events into a schedule.
+pub mod plan;
+
+/// The scheduling algorithm, and the events it produces.
+pub mod scheduler;
+
+/// What to work on right now.
+pub mod slot;
+
+/// Free time and work that didn't fit.
+pub mod summary;
+
+/// Taskwarrior tasks.
+pub mod task;
+
+/// Talking to the `task` binary.
+pub mod taskwarrior;
+
+/// Building tasks in tests, here and in `tests/`. Not part of the API.
+#[doc(hidden)]
+pub mod test_support;
+
+/// Time tracked in Timewarrior.
+pub mod timewarrior;
+
+/// Times of day reserved for certain kinds of work.
+pub mod windows;
+
+pub use caldotcom::CalDotCom;
+pub use plan::{plan, Inputs};
+pub use scheduler::{Event, EventData, Scheduler};
+pub use task::Task;
+pub use taskwarrior::Taskwarrior;
@@ -0,0 +1,42 @@
+use chrono::DateTime;
+use chrono_tz::Tz;
+use color_eyre::eyre::{Context, Result};
+use task_sched::scheduler::Scheduler;
+use task_sched::slot::Slot;
+
+#[derive(Clone, clap::Args)]
+pub struct Next {
+    /// How to print the slot.
+    #[clap(long, value_enum, default_value_t)]
+    format: Format,
+}
+
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+enum Format {
+    /// A few lines for reading in a terminal.
+    #[default]
+    Text,
+
+    /// A single line for status bars like tmux or polybar.
+    Oneline,
+
+    /// JSON, for other tools.
+    Json,
+}
+
+impl Next {
+    pub fn run(&self, scheduler: &Scheduler, now: DateTime<Tz>) -> Result<()> {
+        let slot = Slot::at(scheduler, now);
+
+        match self.format {
+            Format::Text => print!("{}", slot.text(now)),
+            Format::Oneline => println!("{}", slot.oneline(now)),
+            Format::Json => println!(
+                "{}",
+                serde_json::to_string(&slot).wrap_err("could not serialize slot")?
+            ),
+        }
+
+        Ok(())
+    }
+}
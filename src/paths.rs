@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+/// Where to keep things between runs, following the XDG base directory
+/// spec: `$XDG_STATE_HOME/task-sched`, or `~/.local/state/task-sched`.
+pub fn state_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+/// Where to keep copies of things we could fetch again, like calendar
+/// responses: `$XDG_CACHE_HOME/task-sched`, or `~/.cache/task-sched`.
+pub fn cache_dir() -> Option<PathBuf> {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+fn xdg_dir(var: &str, fallback: &str) -> Option<PathBuf> {
+    let base = match std::env::var_os(var).filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(fallback),
+    };
+
+    Some(base.join("task-sched"))
+}
@@ -0,0 +1,755 @@
+use crate::accuracy::Corrections;
+use crate::budget::{Budget, Budgets};
+use crate::calendar::{BlockedTime, StatusPolicy, Treatment};
+use crate::config::Config;
+use crate::scheduler::{Buffers, Scheduler};
+use crate::task::Task;
+use crate::windows::Windows;
+use chrono::{DateTime, Duration, Weekday};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+
+/// Everything the scheduler needs to make a plan: when to plan for, what's
+/// already on the calendar, what needs doing, and preferences for how to fit
+/// it all in. Start with [`Inputs::new`] and add to it with the `with_*`
+/// methods.
+#[derive(Debug, Clone)]
+pub struct Inputs {
+    pub start: DateTime<Tz>,
+    pub end: DateTime<Tz>,
+
+    /// Days with working hours. Everything outside them is off.
+    pub work_days: Vec<Weekday>,
+
+    /// When work starts and ends on work days, as `(hour, minute)`.
+    pub work_start: (u8, u8),
+    pub work_end: (u8, u8),
+
+    pub tw_config: Config,
+    pub busy: Vec<BlockedTime>,
+
+    /// Which calendar events block time, which shorten working hours, and
+    /// which don't matter.
+    pub statuses: StatusPolicy,
+    pub tasks: Vec<Task>,
+
+    pub windows: Windows,
+    pub budgets: Vec<Budget>,
+    pub buffers: Buffers,
+    pub corrections: Corrections,
+
+    /// Time already spent on tasks, by UUID.
+    pub spent: HashMap<String, Duration>,
+
+    /// The UUID of the task being worked on right now, if any.
+    pub active: Option<String>,
+
+    /// Whether to record why each task was picked. See
+    /// [`Scheduler::decisions`].
+    pub decision_log: bool,
+}
+
+impl Inputs {
+    /// Plan from `start` to `end`, working 9:00 to 17:30 Monday through
+    /// Friday, with no tasks or calendar events yet.
+    pub fn new(start: DateTime<Tz>, end: DateTime<Tz>) -> Self {
+        Self {
+            start,
+            end,
+            work_days: vec![
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ],
+            work_start: (9, 0),
+            work_end: (17, 30),
+            tw_config: Config::default(),
+            busy: Vec::new(),
+            statuses: StatusPolicy::default(),
+            tasks: Vec::new(),
+            windows: Windows::default(),
+            budgets: Vec::new(),
+            buffers: Buffers::default(),
+            corrections: Corrections::default(),
+            spent: HashMap::new(),
+            active: None,
+            decision_log: false,
+        }
+    }
+
+    pub fn with_work_hours(
+        mut self,
+        work_days: Vec<Weekday>,
+        work_start: (u8, u8),
+        work_end: (u8, u8),
+    ) -> Self {
+        self.work_days = work_days;
+        self.work_start = work_start;
+        self.work_end = work_end;
+
+        self
+    }
+
+    pub fn with_config(mut self, tw_config: Config) -> Self {
+        self.tw_config = tw_config;
+
+        self
+    }
+
+    pub fn with_busy(mut self, busy: Vec<BlockedTime>) -> Self {
+        self.busy = busy;
+
+        self
+    }
+
+    pub fn with_status_policy(mut self, statuses: StatusPolicy) -> Self {
+        self.statuses = statuses;
+
+        self
+    }
+
+    pub fn with_tasks(mut self, tasks: Vec<Task>) -> Self {
+        self.tasks = tasks;
+
+        self
+    }
+
+    pub fn with_windows(mut self, windows: Windows) -> Self {
+        self.windows = windows;
+
+        self
+    }
+
+    pub fn with_budgets(mut self, budgets: Vec<Budget>) -> Self {
+        self.budgets = budgets;
+
+        self
+    }
+
+    pub fn with_buffers(mut self, buffers: Buffers) -> Self {
+        self.buffers = buffers;
+
+        self
+    }
+
+    pub fn with_corrections(mut self, corrections: Corrections) -> Self {
+        self.corrections = corrections;
+
+        self
+    }
+
+    pub fn with_spent_time(mut self, spent: HashMap<String, Duration>) -> Self {
+        self.spent = spent;
+
+        self
+    }
+
+    pub fn with_active_task(mut self, active: Option<String>) -> Self {
+        self.active = active;
+
+        self
+    }
+
+    pub fn with_decision_log(mut self) -> Self {
+        self.decision_log = true;
+
+        self
+    }
+}
+
+/// Schedule everything in `inputs`. This doesn't talk to Taskwarrior or any
+/// calendar, so it's safe to call as often as you like (say, to try out
+/// changes before making them for real.) The plan is in
+/// [`Scheduler::commitments`].
+pub fn plan(inputs: Inputs) -> Scheduler {
+    let tz = inputs.start.timezone();
+    let mut scheduler = Scheduler::new(
+        inputs.start,
+        inputs.end,
+        inputs.work_days,
+        inputs.work_start,
+        inputs.work_end,
+        inputs.tw_config,
+    )
+    .with_windows(inputs.windows)
+    .with_budgets(Budgets::new(inputs.budgets))
+    .with_buffers(inputs.buffers)
+    .with_corrections(inputs.corrections)
+    .with_spent_time(inputs.spent)
+    .with_active_task(inputs.active);
+
+    if inputs.decision_log {
+        scheduler = scheduler.with_decision_log();
+    }
+
+    for busy_time in inputs.busy {
+        let start = busy_time.start.with_timezone(&tz);
+        let end = busy_time.end.with_timezone(&tz);
+
+        match inputs.statuses.treatment(busy_time.status) {
+            Treatment::Block => match busy_time.title {
+                Some(title) => scheduler.meeting(start, end, title),
+                None => scheduler.block(start, end),
+            },
+            Treatment::Shorten => scheduler.take_off(start, end),
+            Treatment::Ignore => (),
+        }
+    }
+
+    for task in inputs.tasks {
+        scheduler.add_task(task);
+    }
+
+    scheduler.schedule();
+
+    scheduler
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::calendar::Status;
+    use crate::scheduler::EventData;
+    use crate::test_support::TaskBuilder;
+    use crate::windows::WindowMode;
+    use chrono::TimeZone;
+    use chrono_tz::America::Chicago;
+    use chrono_tz::Asia::Tokyo;
+
+    /// When a task was scheduled, like `Mon 09:00-10:00`.
+    fn times(scheduler: &Scheduler, uuid: &str) -> Vec<String> {
+        scheduler
+            .commitments
+            .iter()
+            .filter(|event| matches!(&event.what, EventData::Task { uuid: id, .. } if id == uuid))
+            .map(|event| {
+                format!(
+                    "{}-{}",
+                    event.start.format("%a %H:%M"),
+                    event.end.format("%H:%M")
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn plans_tasks_around_busy_time() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 4, 9, 0, 0).unwrap();
+        let task = TaskBuilder::new("abc")
+            .with_estimate(Duration::hours(1))
+            .build();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(1))
+                .with_busy(vec![BlockedTime {
+                    start: start.to_utc(),
+                    end: (start + Duration::hours(1)).to_utc(),
+                    status: Status::Busy,
+                    title: None,
+                }])
+                .with_tasks(vec![task]),
+        );
+
+        let first = scheduler
+            .commitments
+            .iter()
+            .find(|event| matches!(event.what, EventData::Task { .. }))
+            .unwrap();
+        assert_eq!(first.start, start + Duration::hours(1));
+        assert_eq!(scheduler.remaining_time("abc"), Some(Duration::zero()));
+    }
+
+    #[test]
+    fn work_hours_are_in_the_time_zone_of_start() {
+        let start = Tokyo.with_ymd_and_hms(2025, 3, 4, 0, 0, 0).unwrap();
+
+        let scheduler = plan(Inputs::new(start, start + Duration::days(1)));
+
+        let first_off = &scheduler.commitments[0];
+        assert_eq!(first_off.what, EventData::OffHours);
+        assert_eq!(
+            first_off.end,
+            Tokyo.with_ymd_and_hms(2025, 3, 4, 9, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn days_are_as_long_as_the_clock_says() {
+        // Daylight saving started on Sunday, March 9, 2025 in Chicago, so
+        // that day was only 23 hours long.
+        let start = Chicago.with_ymd_and_hms(2025, 3, 8, 0, 0, 0).unwrap();
+        let end = Chicago.with_ymd_and_hms(2025, 3, 11, 0, 0, 0).unwrap();
+
+        let scheduler = plan(Inputs::new(start, end));
+
+        // The weekend runs straight into Monday morning: 24 + 23 + 9 hours.
+        let weekend = &scheduler.commitments[0];
+        assert_eq!(weekend.start, start);
+        assert_eq!(
+            weekend.end,
+            Chicago.with_ymd_and_hms(2025, 3, 10, 9, 0, 0).unwrap()
+        );
+        assert_eq!(weekend.end - weekend.start, Duration::hours(56));
+    }
+
+    #[test]
+    fn work_can_start_at_a_skipped_time() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 9, 0, 0, 0).unwrap();
+        let end = Chicago.with_ymd_and_hms(2025, 3, 10, 0, 0, 0).unwrap();
+
+        let scheduler =
+            plan(Inputs::new(start, end).with_work_hours(vec![Weekday::Sun], (2, 30), (17, 0)));
+
+        assert_eq!(
+            scheduler.commitments[0].end,
+            Chicago.with_ymd_and_hms(2025, 3, 9, 3, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn calendar_events_by_status() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 4, 9, 0, 0).unwrap();
+        let event = |hour: i64, status| BlockedTime {
+            start: (start + Duration::hours(hour)).to_utc(),
+            end: (start + Duration::hours(hour + 1)).to_utc(),
+            status,
+            title: None,
+        };
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::hours(8))
+                .with_busy(vec![
+                    event(1, Status::Busy),
+                    event(3, Status::Tentative),
+                    event(5, Status::OutOfOffice),
+                ])
+                .with_buffers(Buffers {
+                    before: Duration::minutes(5),
+                    after: Duration::minutes(5),
+                    min_meeting: Duration::zero(),
+                }),
+        );
+
+        let events: Vec<(i64, &EventData)> = scheduler
+            .commitments
+            .iter()
+            .filter(|event| event.start >= start && event.end <= start + Duration::hours(8))
+            .map(|event| ((event.start - start).num_minutes(), &event.what))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (55, &EventData::Buffer),
+                (60, &EventData::Blocked),
+                (120, &EventData::Buffer),
+                (300, &EventData::OffHours),
+            ]
+        );
+    }
+
+    #[test]
+    fn back_to_back_meetings_share_buffers() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 4, 9, 0, 0).unwrap();
+        let meeting = |minutes: i64, title: &str| BlockedTime {
+            start: (start + Duration::minutes(minutes)).to_utc(),
+            end: (start + Duration::minutes(minutes + 20)).to_utc(),
+            status: Status::Busy,
+            title: Some(title.into()),
+        };
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::hours(8))
+                .with_busy(vec![meeting(60, "Standup"), meeting(80, "1:1")])
+                .with_buffers(Buffers {
+                    before: Duration::minutes(5),
+                    after: Duration::minutes(5),
+                    min_meeting: Duration::minutes(30),
+                }),
+        );
+
+        let events: Vec<(i64, &EventData)> = scheduler
+            .commitments
+            .iter()
+            .filter(|event| event.start >= start && event.end <= start + Duration::hours(8))
+            .map(|event| ((event.start - start).num_minutes(), &event.what))
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                (55, &EventData::Buffer),
+                (
+                    60,
+                    &EventData::Meeting {
+                        title: "Standup".into()
+                    }
+                ),
+                (
+                    80,
+                    &EventData::Meeting {
+                        title: "1:1".into()
+                    }
+                ),
+                (100, &EventData::Buffer),
+            ]
+        );
+    }
+
+    #[test]
+    fn budgets_start_over_each_period() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(3))
+                .with_budgets(vec!["project:work=1h/day".parse().unwrap()])
+                .with_tasks(vec![TaskBuilder::new("report")
+                    .with_estimate(Duration::hours(3))
+                    .with_project("work")
+                    .build()]),
+        );
+
+        assert_eq!(
+            times(&scheduler, "report"),
+            vec!["Mon 09:00-10:00", "Tue 09:00-10:00", "Wed 09:00-10:00"]
+        );
+        assert_eq!(scheduler.remaining_time("report"), Some(Duration::zero()));
+    }
+
+    #[test]
+    fn other_work_fills_in_around_budgets() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(2))
+                .with_budgets(vec!["project:work=2h/week".parse().unwrap()])
+                .with_tasks(vec![
+                    TaskBuilder::new("report")
+                        .with_estimate(Duration::hours(3))
+                        .with_project("work")
+                        .with_urgency(10.0)
+                        .build(),
+                    TaskBuilder::new("chores")
+                        .with_estimate(Duration::hours(1))
+                        .build(),
+                ]),
+        );
+
+        assert_eq!(times(&scheduler, "report"), vec!["Mon 09:00-11:00"]);
+        assert_eq!(times(&scheduler, "chores"), vec!["Mon 11:00-12:00"]);
+        assert_eq!(scheduler.remaining_time("report"), Some(Duration::hours(1)));
+    }
+
+    fn windows(window: &str, mode: WindowMode) -> Windows {
+        Windows::new(vec![window.parse().unwrap()], Vec::new(), mode).unwrap()
+    }
+
+    #[test]
+    fn restricted_tasks_wait_for_their_window() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(1))
+                .with_windows(windows("admin=15:00-", WindowMode::Restrict))
+                .with_tasks(vec![TaskBuilder::new("email")
+                    .with_estimate(Duration::hours(1))
+                    .with(serde_json::json!({"tags": ["admin"]}))
+                    .build()]),
+        );
+
+        assert_eq!(times(&scheduler, "email"), vec!["Mon 15:00-16:00"]);
+    }
+
+    #[test]
+    fn restricted_tasks_stop_when_their_window_closes() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(3))
+                .with_windows(windows("deep=09:00-10:00", WindowMode::Restrict))
+                .with_tasks(vec![TaskBuilder::new("essay")
+                    .with_estimate(Duration::hours(3))
+                    .with(serde_json::json!({"tags": ["deep"]}))
+                    .build()]),
+        );
+
+        assert_eq!(
+            times(&scheduler, "essay"),
+            vec!["Mon 09:00-10:00", "Tue 09:00-10:00", "Wed 09:00-10:00"]
+        );
+    }
+
+    #[test]
+    fn preferred_windows_interrupt_other_work() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(1))
+                .with_windows(windows("deep=10:00-12:00", WindowMode::Prefer))
+                .with_tasks(vec![
+                    TaskBuilder::new("chores")
+                        .with_estimate(Duration::hours(3))
+                        .with_urgency(10.0)
+                        .build(),
+                    TaskBuilder::new("essay")
+                        .with_estimate(Duration::hours(1))
+                        .with(serde_json::json!({"tags": ["deep"]}))
+                        .build(),
+                ]),
+        );
+
+        assert_eq!(
+            times(&scheduler, "chores"),
+            vec!["Mon 09:00-10:00", "Mon 11:00-13:00"]
+        );
+        assert_eq!(times(&scheduler, "essay"), vec!["Mon 10:00-11:00"]);
+    }
+
+    #[test]
+    fn work_continues_through_unrelated_windows() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(1))
+                .with_windows(windows("deep=10:00-12:00", WindowMode::Prefer))
+                .with_tasks(vec![TaskBuilder::new("chores")
+                    .with_estimate(Duration::hours(3))
+                    .build()]),
+        );
+
+        assert_eq!(times(&scheduler, "chores"), vec!["Mon 09:00-12:00"]);
+    }
+
+    #[test]
+    fn tasks_wait_for_notbefore() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        let task = TaskBuilder::new("call")
+            .with_estimate(Duration::hours(1))
+            .with(serde_json::json!({"notbefore": "14:00"}))
+            .build();
+
+        let scheduler = plan(Inputs::new(start, start + Duration::days(1)).with_tasks(vec![task]));
+
+        assert_eq!(times(&scheduler, "call"), vec!["Mon 14:00-15:00"]);
+    }
+
+    #[test]
+    fn tasks_wait_for_an_allowed_weekday() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        let task = TaskBuilder::new("groceries")
+            .with_estimate(Duration::hours(1))
+            .with(serde_json::json!({"weekdays": "thu"}))
+            .build();
+
+        let scheduler = plan(Inputs::new(start, start + Duration::days(5)).with_tasks(vec![task]));
+
+        assert_eq!(times(&scheduler, "groceries"), vec!["Thu 09:00-10:00"]);
+    }
+
+    /// A `+fixed` task scheduled at `hour` on Monday, March 3rd in Chicago.
+    fn pinned(uuid: &str, hour: u32, extra: serde_json::Value) -> Task {
+        let scheduled = Chicago
+            .with_ymd_and_hms(2025, 3, 3, hour, 0, 0)
+            .unwrap()
+            .to_utc()
+            .format("%Y%m%dT%H%M%SZ")
+            .to_string();
+
+        TaskBuilder::new(uuid)
+            .with_estimate(Duration::hours(1))
+            .with(serde_json::json!({"tags": ["fixed"], "scheduled": scheduled}))
+            .with(extra)
+            .build()
+    }
+
+    #[test]
+    fn pinned_tasks_keep_their_slot() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(1)).with_tasks(vec![
+                pinned("dentist", 14, serde_json::json!({})),
+                TaskBuilder::new("report")
+                    .with_estimate(Duration::hours(6))
+                    .build(),
+            ]),
+        );
+
+        assert_eq!(times(&scheduler, "dentist"), vec!["Mon 14:00-15:00"]);
+        assert_eq!(
+            times(&scheduler, "report"),
+            vec!["Mon 09:00-14:00", "Mon 15:00-16:00"]
+        );
+    }
+
+    #[test]
+    fn tasks_wait_for_pinned_dependencies() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(1)).with_tasks(vec![
+                pinned("interview", 14, serde_json::json!({})),
+                TaskBuilder::new("feedback")
+                    .with_estimate(Duration::minutes(30))
+                    .with(serde_json::json!({"depends": ["interview"]}))
+                    .build(),
+            ]),
+        );
+
+        assert_eq!(times(&scheduler, "feedback"), vec!["Mon 15:00-15:30"]);
+        assert_eq!(
+            scheduler.completed_at("interview"),
+            Some(Chicago.with_ymd_and_hms(2025, 3, 3, 15, 0, 0).unwrap())
+        );
+    }
+
+    #[test]
+    fn pinned_tasks_respect_status_and_wait() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(1)).with_tasks(vec![
+                pinned("done", 10, serde_json::json!({"status": "completed"})),
+                pinned(
+                    "hidden",
+                    11,
+                    serde_json::json!({"wait": "20250304T000000Z"}),
+                ),
+            ]),
+        );
+
+        assert!(times(&scheduler, "done").is_empty());
+        assert!(times(&scheduler, "hidden").is_empty());
+    }
+
+    #[test]
+    fn pinned_tasks_on_top_of_meetings_collide() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+        let meeting = Chicago.with_ymd_and_hms(2025, 3, 3, 14, 30, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(1))
+                .with_busy(vec![BlockedTime {
+                    start: meeting.to_utc(),
+                    end: (meeting + Duration::hours(1)).to_utc(),
+                    status: Status::Busy,
+                    title: Some("Standup".into()),
+                }])
+                .with_tasks(vec![
+                    pinned("dentist", 14, serde_json::json!({})),
+                    pinned("lunch", 12, serde_json::json!({})),
+                ]),
+        );
+
+        let collisions: Vec<(String, &EventData)> = scheduler
+            .collisions()
+            .into_iter()
+            .map(|(pinned, busy)| (pinned.start.format("%H:%M").to_string(), &busy.what))
+            .collect();
+        assert_eq!(
+            collisions,
+            vec![(
+                "14:00".to_string(),
+                &EventData::Meeting {
+                    title: "Standup".into()
+                }
+            )]
+        );
+    }
+
+    fn spent(uuid: &str, minutes: i64) -> HashMap<String, Duration> {
+        HashMap::from([(uuid.to_string(), Duration::minutes(minutes))])
+    }
+
+    #[test]
+    fn spent_time_comes_off_the_estimate() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+        let remaining = |minutes| {
+            let scheduler = plan(
+                Inputs::new(start, start + Duration::days(1))
+                    .with_spent_time(spent("report", minutes))
+                    .with_tasks(vec![TaskBuilder::new("report")
+                        .with_estimate(Duration::hours(1))
+                        .build()]),
+            );
+
+            times(&scheduler, "report")
+        };
+
+        // Partway through, even with less than 10 minutes left.
+        assert_eq!(remaining(20), vec!["Mon 09:00-09:40"]);
+        assert_eq!(remaining(55), vec!["Mon 09:00-09:05"]);
+
+        // Right at the estimate, or over it: a little time to wrap up.
+        assert_eq!(remaining(60), vec!["Mon 09:00-09:10"]);
+        assert_eq!(remaining(120), vec!["Mon 09:00-09:10"]);
+    }
+
+    #[test]
+    fn corrections_scale_estimates() {
+        use crate::accuracy::Accuracy;
+        use crate::timewarrior::Interval;
+
+        // Three finished `email` tasks each took an hour instead of 30
+        // minutes.
+        let finished: Vec<Task> = (0..3)
+            .map(|i| {
+                TaskBuilder::new(&format!("done-{i}"))
+                    .with_estimate(Duration::minutes(30))
+                    .with(serde_json::json!({"tags": ["email"]}))
+                    .build()
+            })
+            .collect();
+        let tracked: Vec<Interval> = finished
+            .iter()
+            .map(|task| Interval {
+                start: task.entry,
+                end: Some(task.entry + Duration::hours(1)),
+                tags: vec![task.uuid.clone()],
+            })
+            .collect();
+        let corrections = Accuracy::new(&finished, &tracked, chrono::Utc::now()).corrections();
+
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(1))
+                .with_corrections(corrections)
+                .with_tasks(vec![TaskBuilder::new("inbox")
+                    .with_estimate(Duration::hours(1))
+                    .with(serde_json::json!({"tags": ["email"]}))
+                    .build()]),
+        );
+
+        assert_eq!(times(&scheduler, "inbox"), vec!["Mon 09:00-11:00"]);
+    }
+
+    #[test]
+    fn active_task_goes_first_once_it_can() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(1))
+                .with_active_task(Some("active".into()))
+                .with_tasks(vec![
+                    TaskBuilder::new("active")
+                        .with_estimate(Duration::hours(1))
+                        .with(serde_json::json!({"wait": "20250303T153000Z"}))
+                        .build(),
+                    TaskBuilder::new("urgent")
+                        .with_estimate(Duration::hours(1))
+                        .with_urgency(10.0)
+                        .build(),
+                    TaskBuilder::new("important")
+                        .with_estimate(Duration::hours(1))
+                        .with_urgency(5.0)
+                        .build(),
+                ]),
+        );
+
+        assert_eq!(times(&scheduler, "urgent"), vec!["Mon 09:00-10:00"]);
+        assert_eq!(times(&scheduler, "active"), vec!["Mon 10:00-11:00"]);
+        assert_eq!(times(&scheduler, "important"), vec!["Mon 11:00-12:00"]);
+    }
+}
@@ -1,6 +1,11 @@
+use crate::accuracy::Corrections;
+use crate::budget::Budgets;
 use crate::config::Config;
-use crate::task::Task;
-use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Weekday};
+use crate::dates;
+use crate::task::{Status, Task};
+use crate::windows::{Fit, WindowMode, Windows};
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Weekday};
+use chrono_tz::Tz;
 use std::{
     collections::{HashMap, HashSet},
     fmt::{Display, Write},
@@ -10,16 +15,25 @@ use std::{
 #[derive(Debug)]
 pub struct Scheduler {
     // bounds
-    start: DateTime<Local>,
-    end: DateTime<Local>,
+    start: DateTime<Tz>,
+    end: DateTime<Tz>,
 
     // tasks
     tw_config: Config,
     tasks: HashMap<String, TimedTask>,
     outstanding_tasks: HashSet<String>,
 
+    // preferences
+    windows: Windows,
+    budgets: Budgets,
+    buffers: Buffers,
+    corrections: Corrections,
+    spent: HashMap<String, Duration>,
+    active: Option<String>,
+
     // result
     pub commitments: Vec<Event>,
+    decisions: Option<Vec<Decision>>,
 }
 
 const BREAK_TIME: Duration = Duration::minutes(5);
@@ -30,8 +44,8 @@ impl Scheduler {
         skip(start, end, work_days, work_start, work_end, tw_config)
     )]
     pub fn new(
-        start: DateTime<Local>,
-        end: DateTime<Local>,
+        start: DateTime<Tz>,
+        end: DateTime<Tz>,
         work_days: Vec<Weekday>,
         work_start: (u8, u8),
         work_end: (u8, u8),
@@ -47,79 +61,55 @@ impl Scheduler {
             tasks: HashMap::new(),
             outstanding_tasks: HashSet::new(),
 
+            // preferences
+            windows: Windows::default(),
+            budgets: Budgets::default(),
+            buffers: Buffers::default(),
+            corrections: Corrections::default(),
+            spent: HashMap::new(),
+            active: None,
+
             // result
             commitments: Vec::new(),
+            decisions: None,
         };
 
         let (start_hour, start_minute) = work_start;
         let (end_hour, end_minute) = work_end;
-
-        let mut date = new.start;
-        while date <= new.end {
-            let next_date = date + Duration::days(1);
+        let work_start = NaiveTime::from_hms_opt(start_hour.into(), start_minute.into(), 0)
+            .expect("work should start at a valid time of day");
+        let work_end = NaiveTime::from_hms_opt(end_hour.into(), end_minute.into(), 0)
+            .expect("work should end at a valid time of day");
+
+        // Days aren't always 24 hours long (or don't always have a 2:30) so
+        // we step through calendar dates and find each boundary on its own.
+        let tz = new.start.timezone();
+        let mut date = new.start.date_naive();
+        while dates::midnight(&tz, date) < new.end {
+            let next_date = date.succ_opt().expect("dates should not overflow");
             tracing::trace!(?date, "considering blocked times for date");
 
             if work_days.contains(&date.weekday()) {
                 tracing::trace!(?date, "was a weekday");
 
                 new.commitments.push(Event {
-                    start: Local
-                        .with_ymd_and_hms(date.year(), date.month(), date.day(), 0, 0, 0)
-                        .unwrap(),
-                    end: Local
-                        .with_ymd_and_hms(
-                            date.year(),
-                            date.month(),
-                            date.day(),
-                            start_hour.into(),
-                            start_minute.into(),
-                            0,
-                        )
-                        .unwrap(),
-                    what: EventData::Blocked,
+                    start: dates::midnight(&tz, date),
+                    end: dates::at_local(&tz, date, work_start),
+                    what: EventData::OffHours,
                 });
 
                 new.commitments.push(Event {
-                    start: Local
-                        .with_ymd_and_hms(
-                            date.year(),
-                            date.month(),
-                            date.day(),
-                            end_hour.into(),
-                            end_minute.into(),
-                            0,
-                        )
-                        .unwrap(),
-                    end: Local
-                        .with_ymd_and_hms(
-                            next_date.year(),
-                            next_date.month(),
-                            next_date.day(),
-                            0,
-                            0,
-                            0,
-                        )
-                        .unwrap(),
-                    what: EventData::Blocked,
+                    start: dates::at_local(&tz, date, work_end),
+                    end: dates::midnight(&tz, next_date),
+                    what: EventData::OffHours,
                 });
             } else {
                 tracing::trace!(?date, "was a weekend");
 
                 new.commitments.push(Event {
-                    start: Local
-                        .with_ymd_and_hms(date.year(), date.month(), date.day(), 0, 0, 0)
-                        .unwrap(),
-                    end: Local
-                        .with_ymd_and_hms(
-                            next_date.year(),
-                            next_date.month(),
-                            next_date.day(),
-                            0,
-                            0,
-                            0,
-                        )
-                        .unwrap(),
-                    what: EventData::Blocked,
+                    start: dates::midnight(&tz, date),
+                    end: dates::midnight(&tz, next_date),
+                    what: EventData::OffHours,
                 });
             }
 
@@ -131,16 +121,85 @@ impl Scheduler {
         new
     }
 
-    pub fn block(&mut self, start: DateTime<Local>, end: DateTime<Local>) {
-        if end <= self.start || start >= self.end {
-            return;
-        }
+    pub fn with_windows(mut self, windows: Windows) -> Self {
+        self.windows = windows;
+
+        self
+    }
+
+    pub fn with_budgets(mut self, budgets: Budgets) -> Self {
+        self.budgets = budgets;
+
+        self
+    }
+
+    pub fn with_buffers(mut self, buffers: Buffers) -> Self {
+        self.buffers = buffers;
+
+        self
+    }
+
+    /// Scale estimates by how long tasks have actually been taking.
+    pub fn with_corrections(mut self, corrections: Corrections) -> Self {
+        self.corrections = corrections;
+
+        self
+    }
+
+    /// Subtract time already spent on tasks from their estimates. This has
+    /// to be set before tasks are added.
+    pub fn with_spent_time(mut self, spent: HashMap<String, Duration>) -> Self {
+        self.spent = spent;
+
+        self
+    }
+
+    /// Start the schedule with the task that's being worked on right now.
+    pub fn with_active_task(mut self, uuid: Option<String>) -> Self {
+        self.active = uuid;
+
+        self
+    }
+
+    /// Keep track of every choice the scheduler makes, for explaining the
+    /// plan later. This is off by default since it's a lot of extra work.
+    pub fn with_decision_log(mut self) -> Self {
+        self.decisions = Some(Vec::new());
+
+        self
+    }
 
-        let new_event = Event {
+    pub fn block(&mut self, start: DateTime<Tz>, end: DateTime<Tz>) {
+        self.insert(Event {
             start,
             end,
             what: EventData::Blocked,
-        };
+        });
+    }
+
+    /// Take time out of working hours, like leaving early. Unlike
+    /// [`Scheduler::block`], this doesn't get buffers.
+    pub fn take_off(&mut self, start: DateTime<Tz>, end: DateTime<Tz>) {
+        self.insert(Event {
+            start,
+            end,
+            what: EventData::OffHours,
+        });
+    }
+
+    /// Block off time for a meeting we know the name of.
+    pub fn meeting(&mut self, start: DateTime<Tz>, end: DateTime<Tz>, title: String) {
+        self.insert(Event {
+            start,
+            end,
+            what: EventData::Meeting { title },
+        });
+    }
+
+    fn insert(&mut self, new_event: Event) {
+        if new_event.end <= self.start || new_event.start >= self.end {
+            return;
+        }
 
         for (i, event) in self.commitments.iter().enumerate() {
             if event.start > new_event.start {
@@ -153,11 +212,80 @@ impl Scheduler {
     }
 
     pub fn add_task(&mut self, task: Task) {
+        // Completed and deleted tasks don't need any more time.
+        if task.status != Status::Pending {
+            return;
+        }
+
+        // Fixed tasks are appointments, not work to fit in wherever. They get
+        // exactly their slot and don't compete with anything else.
+        let tz = self.start.timezone();
+        if let Some(start) = task.fixed_start(&tz) {
+            let start = start.with_timezone(&tz);
+
+            if !task.available_at(start.to_utc()) {
+                tracing::warn!(
+                    task = task.description,
+                    %start,
+                    "fixed task is waiting until after it starts, so it isn't scheduled",
+                );
+                return;
+            }
+
+            let estimate = task.estimate.unwrap_or_else(|| {
+                tracing::warn!(
+                    task = task.description,
+                    "fixed task has no estimate, so it only gets 10 minutes",
+                );
+                Duration::minutes(10)
+            });
+
+            self.insert(Event {
+                start,
+                end: start + estimate,
+                what: EventData::Task {
+                    uuid: task.uuid.clone(),
+                    name: task.description.clone(),
+                    is_meta: false,
+                },
+            });
+
+            // There's no time left to schedule, but it isn't done until the
+            // appointment is over. Tasks that depend on it have to wait.
+            self.outstanding_tasks.insert(task.uuid.clone());
+            self.tasks.insert(
+                task.uuid.clone(),
+                TimedTask {
+                    remaining_time: Duration::zero(),
+                    planned_time: estimate,
+                    pinned_until: Some(start + estimate),
+                    task,
+                },
+            );
+
+            return;
+        }
+
+        let estimate = task.estimate.unwrap_or(Duration::minutes(10));
+        let factor = self.corrections.factor_for(&task);
+        let corrected = Duration::seconds((estimate.num_seconds() as f64 * factor).round() as i64);
+
+        // If we've already gone over the estimate, we don't know how much is
+        // left. Schedule a little time to finish up or re-estimate instead of
+        // dropping the task entirely.
+        let remaining_time = match self.spent.get(&task.uuid) {
+            Some(spent) if *spent >= corrected => Duration::minutes(10),
+            Some(spent) => corrected - *spent,
+            None => corrected,
+        };
+
         self.outstanding_tasks.insert(task.uuid.clone());
         self.tasks.insert(
             task.uuid.clone(),
             TimedTask {
-                remaining_time: task.estimate.unwrap_or(Duration::minutes(10)),
+                remaining_time,
+                planned_time: remaining_time,
+                pinned_until: None,
                 task,
             },
         );
@@ -167,6 +295,8 @@ impl Scheduler {
     pub fn schedule(&mut self) {
         // Before we begin, make sure we don't have overlapping blocked time.
         self.simplify();
+        self.warn_about_collisions();
+        self.add_buffers();
 
         let mut commitments = std::mem::take(&mut self.commitments);
         let mut outstanding_tasks = std::mem::take(&mut self.outstanding_tasks);
@@ -218,19 +348,52 @@ impl Scheduler {
 
                 match self.best_task_at(now, &outstanding_tasks) {
                     None => {
-                        tracing::trace!("no tasks left; finishing");
-                        break 'scheduler;
+                        // Nothing can go here right now, but something might
+                        // later (say, when a budget starts over.) Skip ahead
+                        // to then instead of giving up on the rest of the
+                        // schedule.
+                        let Some(until) = self
+                            .next_change(now, &outstanding_tasks)
+                            .filter(|until| *until > now)
+                        else {
+                            tracing::trace!("no tasks left; finishing");
+                            break 'scheduler;
+                        };
+
+                        tracing::trace!(?until, "nothing to do; skipping ahead");
+                        let skipped = (until - now).min(time_available);
+                        now += skipped;
+                        time_available -= skipped;
                     }
-                    Some(task) => {
+                    Some(uuid) => {
+                        let task = self
+                            .tasks
+                            .get_mut(&uuid)
+                            .expect("best_task_at should only return known tasks");
+
                         // If we have dependencies, this is a meta-task and
                         // should just be broken down or complete on the spot
                         // instead of having time schedule for it.
-                        let time_for_task = if task.is_meta() {
+                        let mut time_for_task = if task.is_meta() {
                             time_available.min(Duration::minutes(10))
                         } else {
                             task.remaining_time.min(time_available)
                         };
 
+                        if let Some(allowed_for) = task.allowed_for(&now) {
+                            time_for_task = time_for_task.min(allowed_for);
+                        }
+
+                        if let Some(budget_left) = self.budgets.remaining_for(task, now) {
+                            time_for_task = time_for_task.min(budget_left);
+                        }
+
+                        // A different task might fit better once a window
+                        // opens or closes, so pick again then.
+                        if let Some(boundary) = self.windows.next_boundary(now) {
+                            time_for_task = time_for_task.min(boundary - now);
+                        }
+
                         let event = Event {
                             start: now,
                             end: now + time_for_task,
@@ -241,9 +404,22 @@ impl Scheduler {
                             },
                         };
                         tracing::debug!(?event.start, ?time_for_task, ?event.what, "scheduled task");
-                        commitments.insert(index, event);
 
-                        index += 1;
+                        // Picking the same task again just keeps it going.
+                        match index.checked_sub(1).and_then(|i| commitments.get_mut(i)) {
+                            Some(previous)
+                                if previous.end == now && previous.what == event.what =>
+                            {
+                                previous.end = event.end;
+                            }
+                            _ => {
+                                commitments.insert(index, event);
+                                index += 1;
+                            }
+                        }
+
+                        self.budgets.record(task, now, time_for_task);
+
                         now += time_for_task;
                         time_available -= time_for_task;
 
@@ -302,35 +478,378 @@ impl Scheduler {
         }
     }
 
+    /// Materialize buffers around calendar events. This has to happen after
+    /// `simplify` so that back-to-back meetings get buffers around the whole
+    /// run instead of in between each one.
+    fn add_buffers(&mut self) {
+        if self.buffers.before.is_zero() && self.buffers.after.is_zero() {
+            return;
+        }
+
+        // Meetings with titles don't get merged together, so find runs of
+        // back-to-back ones here instead.
+        let mut runs: Vec<(DateTime<Tz>, DateTime<Tz>)> = Vec::new();
+        for event in self.commitments.iter().filter(|e| e.what.is_meeting()) {
+            match runs.last_mut() {
+                Some((_, end)) if event.start <= *end => *end = (*end).max(event.end),
+                _ => runs.push((event.start, event.end)),
+            }
+        }
+
+        let mut buffers = Vec::new();
+        for (start, end) in runs {
+            if end - start < self.buffers.min_meeting {
+                continue;
+            }
+
+            buffers.push((start - self.buffers.before, start));
+            buffers.push((end, end + self.buffers.after));
+        }
+
+        for (mut start, mut end) in buffers {
+            // Buffers are the first thing to give way: they shouldn't cover
+            // up anything else, including other meetings and working hours.
+            for other in &self.commitments {
+                if other.what == EventData::Buffer || other.end <= start || end <= other.start {
+                    continue;
+                }
+
+                if other.start <= start {
+                    start = start.max(other.end);
+                } else {
+                    end = end.min(other.start);
+                }
+            }
+
+            if start < end {
+                self.insert(Event {
+                    start,
+                    end,
+                    what: EventData::Buffer,
+                });
+            }
+        }
+
+        // Meetings close together will have overlapping buffers, so merge
+        // them back down.
+        self.simplify();
+    }
+
+    /// Fixed tasks that land on top of calendar events, paired with the
+    /// events. Scheduled tasks never overlap anything, so this works before
+    /// or after scheduling.
+    pub fn collisions(&self) -> Vec<(&Event, &Event)> {
+        let mut out = Vec::new();
+
+        for pinned in &self.commitments {
+            if !matches!(pinned.what, EventData::Task { .. }) {
+                continue;
+            }
+
+            for busy in &self.commitments {
+                if busy.what.is_meeting() && busy.start < pinned.end && pinned.start < busy.end {
+                    out.push((pinned, busy));
+                }
+            }
+        }
+
+        out
+    }
+
+    /// If someone's going to be double-booked, we should say something.
+    fn warn_about_collisions(&self) {
+        for (pinned, busy) in self.collisions() {
+            let EventData::Task { name, .. } = &pinned.what else {
+                continue;
+            };
+
+            tracing::warn!(
+                task = name,
+                start = %pinned.start,
+                busy_start = %busy.start,
+                busy_end = %busy.end,
+                "fixed task collides with calendar busy time",
+            );
+        }
+    }
+
+    pub fn start(&self) -> DateTime<Tz> {
+        self.start
+    }
+
+    pub fn end(&self) -> DateTime<Tz> {
+        self.end
+    }
+
+    pub fn task(&self, uuid: &str) -> Option<&Task> {
+        self.tasks.get(uuid).map(|timed| &timed.task)
+    }
+
+    pub fn tasks(&self) -> impl Iterator<Item = &Task> {
+        self.tasks.values().map(|timed| &timed.task)
+    }
+
+    /// How much time the scheduler tried to find for a task. This is the
+    /// estimate after corrections and time already spent, so it can differ
+    /// from the one in Taskwarrior.
+    pub fn planned_time(&self, uuid: &str) -> Option<Duration> {
+        self.tasks.get(uuid).map(|timed| timed.planned_time)
+    }
+
+    /// How much of a task's estimate is left after scheduling.
+    pub fn remaining_time(&self, uuid: &str) -> Option<Duration> {
+        self.tasks.get(uuid).map(|timed| timed.remaining_time)
+    }
+
+    /// When the last of a task's time was scheduled, if all of it fit.
+    pub fn completed_at(&self, uuid: &str) -> Option<DateTime<Tz>> {
+        if self.remaining_time(uuid)? > Duration::zero() {
+            return None;
+        }
+
+        self.commitments
+            .iter()
+            .filter(|event| matches!(&event.what, EventData::Task { uuid: id, .. } if id == uuid))
+            .map(|event| event.end)
+            .max()
+    }
+
+    pub fn tw_config(&self) -> &Config {
+        &self.tw_config
+    }
+
+    /// Every choice made during scheduling, if `with_decision_log` was set.
+    pub fn decisions(&self) -> &[Decision] {
+        self.decisions.as_deref().unwrap_or_default()
+    }
+
     fn best_task_at(
         &mut self,
-        when: DateTime<Local>,
+        when: DateTime<Tz>,
         outstanding_tasks: &HashSet<String>,
-    ) -> Option<&mut TimedTask> {
+    ) -> Option<String> {
+        let mut candidates = Vec::with_capacity(self.tasks.len());
+        let mut excluded = Vec::new();
+
+        for task in self.tasks.values() {
+            match self.exclusion(task, when, outstanding_tasks) {
+                // Finished tasks aren't interesting to explain, so they
+                // don't go in the decision log.
+                Some(Exclusion::Finished) => (),
+                Some(exclusion) => excluded.push((task.uuid.clone(), exclusion)),
+                None => candidates.push(Candidate {
+                    uuid: task.uuid.clone(),
+                    fit: self.windows.fit(&task.tags, when.time()),
+                    urgency: task.urgency_at(when.to_utc(), &self.tw_config),
+                }),
+            }
+        }
+
+        // Whatever's in progress goes first, whenever it can. After that,
+        // tasks that fit the current window win over everything else, and
+        // tasks that would rather be somewhere else only get picked when
+        // nothing else is available. Urgency breaks ties within each, and
+        // the UUID breaks ties after that so the same inputs always make the
+        // same plan.
+        let best = self
+            .active
+            .clone()
+            .filter(|active| candidates.iter().any(|c| &c.uuid == active))
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .max_by(|a, b| {
+                        a.fit
+                            .cmp(&b.fit)
+                            .then(a.urgency.total_cmp(&b.urgency))
+                            .then_with(|| b.uuid.cmp(&a.uuid))
+                    })
+                    .map(|candidate| candidate.uuid.clone())
+            });
+
+        if let Some(decisions) = &mut self.decisions {
+            decisions.push(Decision {
+                at: when,
+                chosen: best.clone(),
+                candidates,
+                excluded,
+            })
+        }
+
+        best
+    }
+
+    /// The next time after `now` when a task that can't be scheduled right
+    /// now might be, or `None` if that will never happen.
+    fn next_change(
+        &self,
+        now: DateTime<Tz>,
+        outstanding_tasks: &HashSet<String>,
+    ) -> Option<DateTime<Tz>> {
         self.tasks
-            .values_mut()
-            .filter(|task| task.available())
-            .filter(|task| task.available_at(when.to_utc()))
-            .filter(|task| {
-                outstanding_tasks
-                    .intersection(&task.depends)
-                    .next()
-                    .is_none()
+            .values()
+            .filter_map(|task| match self.exclusion(task, now, outstanding_tasks)? {
+                Exclusion::Waiting(until) => Some(until),
+                Exclusion::OverBudget => self.budgets.renews(task, now),
+                Exclusion::OutOfWindow => self.windows.next_boundary(now),
+                Exclusion::NotAllowed => task.next_allowed(&now),
+                // Other blockers have their own reasons in this list, but
+                // appointments are only over when they end.
+                Exclusion::BlockedBy(blockers) => blockers
+                    .iter()
+                    .filter_map(|uuid| self.tasks.get(uuid)?.pinned_until)
+                    .min(),
+                Exclusion::Finished => None,
             })
-            .map(|task| {
-                let urgency = task.urgency_at(when.to_utc(), &self.tw_config);
+            .min()
+    }
+
+    /// Why a task can't be scheduled at the given time, if anything.
+    fn exclusion(
+        &self,
+        task: &TimedTask,
+        when: DateTime<Tz>,
+        outstanding_tasks: &HashSet<String>,
+    ) -> Option<Exclusion> {
+        if !task.available() {
+            return Some(Exclusion::Finished);
+        }
+
+        if let Some(wait) = task.wait.filter(|_| !task.available_at(when.to_utc())) {
+            return Some(Exclusion::Waiting(wait.with_timezone(&when.timezone())));
+        }
+
+        if !task.allowed_at(&when) {
+            return Some(Exclusion::NotAllowed);
+        }
 
-                (task, urgency)
+        if self.budgets.remaining_for(task, when) == Some(Duration::zero()) {
+            return Some(Exclusion::OverBudget);
+        }
+
+        let mut blockers: Vec<String> = outstanding_tasks
+            .intersection(&task.depends)
+            .filter(|dep| {
+                self.tasks
+                    .get(*dep)
+                    .and_then(|dep| dep.pinned_until)
+                    .is_none_or(|end| end > when)
             })
-            .max_by(|(_, urg_a), (_, urg_b)| urg_a.total_cmp(urg_b))
-            .map(|(task, _)| task)
+            .cloned()
+            .collect();
+        if !blockers.is_empty() {
+            blockers.sort();
+            return Some(Exclusion::BlockedBy(blockers));
+        }
+
+        if self.windows.mode() == WindowMode::Restrict
+            && self.windows.fit(&task.tags, when.time()) == Fit::OutOfWindow
+        {
+            return Some(Exclusion::OutOfWindow);
+        }
+
+        None
     }
 }
 
+/// The plan, a day at a time. Long stretches of off hours and meetings are
+/// left out since they're just noise.
+impl Display for Scheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut day = None;
+
+        for commitment in &self.commitments {
+            let date = commitment.start.date_naive();
+            if day != Some(date) {
+                day = Some(date);
+                writeln!(f, "\n########## {} ##########\n", date.format("%b %d"))?;
+            }
+
+            if commitment.what.has_info_for_humans()
+                || commitment.duration() <= Duration::minutes(120)
+            {
+                writeln!(f, "{commitment}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A point where the scheduler picked a task to work on.
+#[derive(Debug)]
+pub struct Decision {
+    pub at: DateTime<Tz>,
+    pub chosen: Option<String>,
+    pub candidates: Vec<Candidate>,
+    pub excluded: Vec<(String, Exclusion)>,
+}
+
+#[derive(Debug)]
+pub struct Candidate {
+    pub uuid: String,
+    pub fit: Fit,
+    pub urgency: f64,
+}
+
+/// Reasons a task wasn't considered at some point in the schedule.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Exclusion {
+    /// All of the task's time has been scheduled already.
+    Finished,
+
+    /// The task is waiting until the given time.
+    Waiting(DateTime<Tz>),
+
+    /// The task's `notbefore`, `notafter`, or `weekdays` rule this out.
+    NotAllowed,
+
+    /// The task's project or tag has used up its budget for now.
+    OverBudget,
+
+    /// The task depends on these other tasks, which aren't done yet.
+    BlockedBy(Vec<String>),
+
+    /// The task is restricted to windows that aren't open.
+    OutOfWindow,
+}
+
+impl Display for Exclusion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Finished => f.write_str("already fully scheduled"),
+            Self::Waiting(until) => {
+                write!(f, "waiting until {}", until.format("%b %-d, %-I:%M %P"))
+            }
+            Self::NotAllowed => f.write_str("outside its notbefore/notafter/weekdays"),
+            Self::OverBudget => f.write_str("over budget"),
+            Self::BlockedBy(uuids) => write!(f, "blocked by {}", uuids.join(", ")),
+            Self::OutOfWindow => f.write_str("outside its time windows"),
+        }
+    }
+}
+
+/// Time to prepare for and decompress after calendar events.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Buffers {
+    pub before: Duration,
+    pub after: Duration,
+
+    /// Only meetings at least this long get buffers.
+    pub min_meeting: Duration,
+}
+
 #[derive(Debug)]
 struct TimedTask {
     task: Task,
     remaining_time: Duration,
+
+    /// How much time we set out to find for the task: its estimate after
+    /// corrections, less any time already spent.
+    planned_time: Duration,
+
+    /// When a fixed task's appointment ends.
+    pinned_until: Option<DateTime<Tz>>,
 }
 
 impl std::ops::Deref for TimedTask {
@@ -355,10 +874,14 @@ impl TimedTask {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Event {
-    pub start: DateTime<Local>,
-    pub end: DateTime<Local>,
+    /// Read back in UTC, since JSON only records the offset. Use
+    /// `with_timezone` to get the original time zone back.
+    #[serde(deserialize_with = "crate::dates::any_offset")]
+    pub start: DateTime<Tz>,
+    #[serde(deserialize_with = "crate::dates::any_offset")]
+    pub end: DateTime<Tz>,
     pub what: EventData,
 }
 
@@ -379,7 +902,15 @@ impl Display for Event {
                 f.write_str(" break) ----------\n")
             }
 
-            EventData::Blocked => {
+            EventData::Buffer => {
+                f.write_str("~~~~~~~~~~ ")?;
+                self.start.format("%-I:%M %P").fmt(f)?;
+                f.write_str(" (")?;
+                f.write_str(&human_time(self.duration()))?;
+                f.write_str(" buffer) ~~~~~~~~~~\n")
+            }
+
+            EventData::Blocked | EventData::OffHours => {
                 f.write_str("\n========== ")?;
                 self.start.format("%-I:%M %P").fmt(f)?;
                 f.write_str(" (")?;
@@ -387,6 +918,16 @@ impl Display for Event {
                 f.write_str(" block) ==========\n")
             }
 
+            EventData::Meeting { title } => {
+                f.write_str("\n========== ")?;
+                self.start.format("%-I:%M %P").fmt(f)?;
+                f.write_str(" (")?;
+                f.write_str(&human_time(self.duration()))?;
+                f.write_str(") ")?;
+                f.write_str(title)?;
+                f.write_str(" ==========\n")
+            }
+
             EventData::Task { name, is_meta, .. } => {
                 self.start.format("%b %-d, %_I:%M %P").fmt(f)?;
                 f.write_str(" (")?;
@@ -413,9 +954,15 @@ impl Display for Event {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
 pub enum EventData {
     Blocked,
+    Meeting {
+        title: String,
+    },
+    OffHours,
+    Buffer,
     Break,
     Task {
         uuid: String,
@@ -427,13 +974,26 @@ pub enum EventData {
 impl EventData {
     pub fn has_info_for_humans(&self) -> bool {
         match self {
-            Self::Blocked | Self::Break => false,
-            Self::Task { .. } => true,
+            Self::Blocked | Self::OffHours | Self::Buffer | Self::Break => false,
+            Self::Meeting { .. } | Self::Task { .. } => true,
         }
     }
+
+    /// Whether this event takes time away from working on tasks.
+    pub fn is_unavailable(&self) -> bool {
+        match self {
+            Self::Blocked | Self::Meeting { .. } | Self::OffHours | Self::Buffer => true,
+            Self::Break | Self::Task { .. } => false,
+        }
+    }
+
+    /// Whether this is something from the calendar, with or without a title.
+    pub fn is_meeting(&self) -> bool {
+        matches!(self, Self::Blocked | Self::Meeting { .. })
+    }
 }
 
-fn human_time(duration: Duration) -> String {
+pub fn human_time(duration: Duration) -> String {
     let mut minutes = duration.num_minutes() as f64;
 
     if minutes < 60.0 {
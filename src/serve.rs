@@ -0,0 +1,307 @@
+use crate::Cli;
+use axum::{
+    extract::{Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::Duration;
+use color_eyre::eyre::{Context, Report, Result};
+use std::{net::SocketAddr, sync::Arc, time::Instant};
+use task_sched::scheduler::Scheduler;
+use task_sched::slot::Slot;
+use task_sched::summary::Summary;
+use task_sched::{dates, ics};
+use tokio::sync::Mutex;
+
+#[derive(Clone, clap::Args)]
+pub struct Serve {
+    /// Where to listen. Anyone who can reach this address can see your
+    /// schedule, so it's best left on localhost.
+    #[clap(long, default_value = "127.0.0.1:7979")]
+    listen: SocketAddr,
+
+    /// Reuse a schedule for this long before making a new one. Add
+    /// `?refresh=true` to any request to re-plan right away.
+    #[clap(long, value_parser = dates::parse_duration, default_value = "5m")]
+    cache_for: Duration,
+}
+
+struct Shared {
+    cli: Cli,
+    cache_for: std::time::Duration,
+    plan: Mutex<Option<(Instant, Arc<Scheduler>)>>,
+}
+
+impl Shared {
+    /// The cached schedule, or a new one if it's too old (or the caller
+    /// asked for a fresh one.)
+    async fn plan(&self, refresh: bool) -> Result<Arc<Scheduler>> {
+        // Holding the lock while planning means a burst of requests makes
+        // one plan instead of one each.
+        let mut plan = self.plan.lock().await;
+
+        if let Some((made, scheduler)) = &*plan {
+            if !refresh && made.elapsed() < self.cache_for {
+                return Ok(scheduler.clone());
+            }
+        }
+
+        tracing::debug!("making a new plan");
+        let scheduler = Arc::new(self.cli.schedule(self.cli.start()).await?);
+        *plan = Some((Instant::now(), scheduler.clone()));
+
+        Ok(scheduler)
+    }
+}
+
+#[derive(serde::Deserialize)]
+struct Refresh {
+    #[serde(default)]
+    refresh: bool,
+}
+
+impl Serve {
+    pub async fn run(&self, cli: &Cli) -> Result<()> {
+        let shared = Arc::new(Shared {
+            cli: cli.clone(),
+            cache_for: self.cache_for.to_std().unwrap_or_default(),
+            plan: Mutex::new(None),
+        });
+
+        let listener = tokio::net::TcpListener::bind(self.listen)
+            .await
+            .wrap_err_with(|| format!("could not listen on {}", self.listen))?;
+
+        tracing::info!("serving on http://{}", listener.local_addr()?);
+
+        axum::serve(listener, router(shared))
+            .await
+            .wrap_err("server stopped")
+    }
+}
+
+fn router(shared: Arc<Shared>) -> Router {
+    Router::new()
+        .route("/commitments", get(commitments))
+        .route("/next", get(next))
+        .route("/unscheduled", get(unscheduled))
+        .route("/schedule.ics", get(calendar))
+        .with_state(shared)
+}
+
+/// Everything on the schedule: tasks, meetings, breaks, and off hours.
+async fn commitments(
+    State(shared): State<Arc<Shared>>,
+    Query(query): Query<Refresh>,
+) -> Result<Json<serde_json::Value>, Error> {
+    let scheduler = shared.plan(query.refresh).await?;
+
+    Ok(Json(serde_json::to_value(&scheduler.commitments)?))
+}
+
+/// The same slot `task-sched next --format json` prints.
+async fn next(
+    State(shared): State<Arc<Shared>>,
+    Query(query): Query<Refresh>,
+) -> Result<Json<serde_json::Value>, Error> {
+    let scheduler = shared.plan(query.refresh).await?;
+    let now = shared.cli.start();
+
+    Ok(Json(serde_json::to_value(Slot::at(&scheduler, now))?))
+}
+
+/// Tasks that didn't fit in the schedule, and how much of them didn't.
+async fn unscheduled(
+    State(shared): State<Arc<Shared>>,
+    Query(query): Query<Refresh>,
+) -> Result<Json<serde_json::Value>, Error> {
+    let scheduler = shared.plan(query.refresh).await?;
+
+    let unscheduled: Vec<serde_json::Value> = Summary::new(&scheduler)
+        .unscheduled
+        .into_iter()
+        .map(|task| {
+            serde_json::json!({
+                "uuid": task.uuid,
+                "description": task.description,
+                "remaining_minutes": task.remaining.num_minutes(),
+                "needed_minutes": task.needed.num_minutes(),
+            })
+        })
+        .collect();
+
+    Ok(Json(serde_json::Value::Array(unscheduled)))
+}
+
+/// Scheduled tasks as an iCalendar feed, for subscribing from a calendar app.
+async fn calendar(
+    State(shared): State<Arc<Shared>>,
+    Query(query): Query<Refresh>,
+) -> Result<Response, Error> {
+    let scheduler = shared.plan(query.refresh).await?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        ics::render(&scheduler.commitments, shared.cli.now()),
+    )
+        .into_response())
+}
+
+/// Planning can fail for all the usual reasons (Taskwarrior missing, the
+/// calendar being down...) and the caller should hear about it.
+struct Error(Report);
+
+impl<E: Into<Report>> From<E> for Error {
+    fn from(err: E) -> Self {
+        Self(err.into())
+    }
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        tracing::error!("{:?}", self.0);
+
+        (StatusCode::INTERNAL_SERVER_ERROR, format!("{:#}\n", self.0)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use chrono::TimeZone;
+    use chrono_tz::America::Chicago;
+    use clap::Parser;
+    use task_sched::test_support::TaskBuilder;
+    use task_sched::{plan, Inputs};
+    use tower::ServiceExt;
+
+    fn cli(extra: &[&str]) -> Cli {
+        let args = [
+            "task-sched",
+            "--start",
+            "2025-03-03T09:00:00-06:00",
+            "--timezone",
+            "America/Chicago",
+        ];
+        Cli::parse_from(args.iter().chain(extra))
+    }
+
+    /// A server with a plan already made for Monday: an hour of writing,
+    /// then a review that doesn't fit.
+    fn shared() -> Arc<Shared> {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::minutes(8 * 60 + 30)).with_tasks(vec![
+                TaskBuilder::new("write")
+                    .with_estimate(Duration::hours(1))
+                    .with_urgency(2.0)
+                    .build(),
+                TaskBuilder::new("review")
+                    .with_estimate(Duration::hours(10))
+                    .build(),
+            ]),
+        );
+
+        Arc::new(Shared {
+            cli: cli(&[]),
+            cache_for: std::time::Duration::from_secs(3600),
+            plan: Mutex::new(Some((Instant::now(), Arc::new(scheduler)))),
+        })
+    }
+
+    async fn get(shared: Arc<Shared>, uri: &str) -> (StatusCode, String, String) {
+        let response = router(shared)
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .map(|value| value.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+
+        (
+            status,
+            content_type,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    async fn get_json(uri: &str) -> serde_json::Value {
+        let (status, content_type, body) = get(shared(), uri).await;
+
+        assert_eq!(status, StatusCode::OK, "{body}");
+        assert_eq!(content_type, "application/json");
+        serde_json::from_str(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn commitments() {
+        let json = get_json("/commitments").await;
+
+        let names: Vec<&str> = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter(|event| event["what"]["kind"] == "task")
+            .filter_map(|event| event["what"]["name"].as_str())
+            .collect();
+        assert_eq!(names, vec!["write", "review"]);
+    }
+
+    #[tokio::test]
+    async fn next() {
+        let json = get_json("/next").await;
+
+        assert_eq!(json["current"]["what"]["name"], "write");
+        assert_eq!(json["then"]["what"]["name"], "review");
+    }
+
+    #[tokio::test]
+    async fn unscheduled() {
+        let json = get_json("/unscheduled").await;
+
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "uuid": "review",
+                "description": "review",
+                "remaining_minutes": 150,
+                "needed_minutes": 600,
+            }])
+        );
+    }
+
+    #[tokio::test]
+    async fn calendar() {
+        let (status, content_type, body) = get(shared(), "/schedule.ics").await;
+
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "text/calendar; charset=utf-8");
+        assert!(body.starts_with("BEGIN:VCALENDAR"), "{body}");
+        assert!(body.contains("SUMMARY:write"), "{body}");
+    }
+
+    #[tokio::test]
+    async fn planning_errors_are_server_errors() {
+        let shared = Arc::new(Shared {
+            cli: cli(&["--taskwarrior-binary", "/nonexistent/task"]),
+            cache_for: std::time::Duration::from_secs(3600),
+            plan: Mutex::new(None),
+        });
+
+        let (status, _, body) = get(shared, "/commitments?refresh=true").await;
+
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(!body.is_empty());
+    }
+}
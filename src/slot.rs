@@ -0,0 +1,236 @@
+use crate::scheduler::{human_time, Event, EventData, Scheduler};
+use chrono::DateTime;
+use chrono_tz::Tz;
+
+/// What's happening at a particular moment in a schedule, and what's coming
+/// up next.
+#[derive(Debug, serde::Serialize)]
+pub struct Slot<'a> {
+    /// Whatever is happening right now: a task, a meeting, a break...
+    pub current: Option<&'a Event>,
+
+    /// The task happening now, or the next one if we're busy with something
+    /// else at the moment.
+    pub task: Option<&'a Event>,
+
+    /// The next calendar event that hasn't started yet.
+    pub next_meeting: Option<&'a Event>,
+
+    /// The first different task after `task`.
+    pub then: Option<&'a Event>,
+}
+
+impl<'a> Slot<'a> {
+    pub fn at(scheduler: &'a Scheduler, now: DateTime<Tz>) -> Self {
+        let upcoming = || scheduler.commitments.iter().filter(move |e| e.end > now);
+
+        let current = upcoming().next().filter(|e| e.start <= now);
+
+        let task = upcoming().find(|e| matches!(e.what, EventData::Task { .. }));
+
+        let next_meeting = upcoming().find(|e| e.what.is_meeting() && e.start > now);
+
+        let then = task.and_then(|task| {
+            upcoming()
+                .filter(|e| e.start >= task.end)
+                .find(|e| matches!(&e.what, EventData::Task { .. }) && e.what != task.what)
+        });
+
+        Self {
+            current,
+            task,
+            next_meeting,
+            then,
+        }
+    }
+
+    /// A few lines for reading in a terminal.
+    pub fn text(&self, now: DateTime<Tz>) -> String {
+        let mut out = String::new();
+
+        match self.current {
+            Some(current) => out.push_str(&format!(
+                "Now:          {} until {} ({} left)\n",
+                describe(current),
+                current.end.format("%-I:%M %P"),
+                human_time(current.end - now),
+            )),
+            None => out.push_str("Now:          nothing scheduled\n"),
+        }
+
+        if let Some(task) = self.task.filter(|task| task.start > now) {
+            out.push_str(&format!(
+                "Next task:    {} at {} ({})\n",
+                describe(task),
+                task.start.format("%-I:%M %P"),
+                human_time(task.duration()),
+            ))
+        }
+
+        if let Some(meeting) = self.next_meeting {
+            out.push_str(&format!(
+                "Next meeting: {} (in {})\n",
+                meeting.start.format("%-I:%M %P"),
+                human_time(meeting.start - now),
+            ))
+        }
+
+        if let Some(then) = self.then {
+            out.push_str(&format!(
+                "Then:         {} at {}\n",
+                describe(then),
+                then.start.format("%-I:%M %P"),
+            ))
+        }
+
+        out
+    }
+
+    /// A single line for status bars.
+    pub fn oneline(&self, now: DateTime<Tz>) -> String {
+        let mut parts = Vec::with_capacity(3);
+
+        match (self.current, self.task) {
+            (Some(current), _) if matches!(current.what, EventData::Task { .. }) => {
+                parts.push(format!(
+                    "{} ({} left)",
+                    describe(current),
+                    human_time(current.end - now)
+                ))
+            }
+            (Some(current), Some(task)) => parts.push(format!(
+                "{} until {}, then {}",
+                describe(current),
+                current.end.format("%-I:%M %P"),
+                describe(task),
+            )),
+            (None, Some(task)) => parts.push(format!(
+                "{} at {}",
+                describe(task),
+                task.start.format("%-I:%M %P")
+            )),
+            (Some(current), None) => parts.push(format!(
+                "{} until {}",
+                describe(current),
+                current.end.format("%-I:%M %P"),
+            )),
+            (None, None) => parts.push("nothing scheduled".into()),
+        }
+
+        if let Some(meeting) = self.next_meeting {
+            parts.push(format!("meeting in {}", human_time(meeting.start - now)))
+        }
+
+        if let Some(then) = self.then {
+            parts.push(format!("next: {}", describe(then)))
+        }
+
+        parts.join(" | ")
+    }
+}
+
+fn describe(event: &Event) -> String {
+    match &event.what {
+        EventData::Task { name, is_meta, .. } => {
+            if *is_meta {
+                format!("META - {name}")
+            } else {
+                name.clone()
+            }
+        }
+        EventData::Blocked => "meeting".into(),
+        EventData::Meeting { title } => title.clone(),
+        EventData::OffHours => "off".into(),
+        EventData::Buffer => "buffer".into(),
+        EventData::Break => "break".into(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::calendar::{BlockedTime, Status};
+    use crate::plan::{plan, Inputs};
+    use crate::test_support::TaskBuilder;
+    use chrono::{Duration, TimeZone};
+    use chrono_tz::America::Chicago;
+
+    fn at(h: u32, m: u32) -> DateTime<Tz> {
+        Chicago.with_ymd_and_hms(2025, 3, 3, h, m, 0).unwrap()
+    }
+
+    /// Writing from 9 to 10, planning from 11 to 12, and reviewing (which
+    /// has to wait) from 1 to 2. Nothing in between or after.
+    fn scheduler() -> Scheduler {
+        plan(
+            Inputs::new(at(9, 0), at(17, 30))
+                .with_busy(vec![BlockedTime {
+                    start: at(11, 0).to_utc(),
+                    end: at(12, 0).to_utc(),
+                    status: Status::Busy,
+                    title: Some("Planning".into()),
+                }])
+                .with_tasks(vec![
+                    TaskBuilder::new("write")
+                        .with_estimate(Duration::hours(1))
+                        .with_urgency(2.0)
+                        .build(),
+                    TaskBuilder::new("review")
+                        .with_estimate(Duration::hours(1))
+                        .with(serde_json::json!({"wait": "20250303T190000Z"}))
+                        .build(),
+                ]),
+        )
+    }
+
+    fn name(event: Option<&Event>) -> Option<String> {
+        event.map(describe)
+    }
+
+    #[test]
+    fn inside_a_task() {
+        let scheduler = scheduler();
+        let now = at(9, 30);
+        let slot = Slot::at(&scheduler, now);
+
+        assert_eq!(name(slot.current), Some("write".into()));
+        assert_eq!(name(slot.task), Some("write".into()));
+        assert_eq!(name(slot.next_meeting), Some("Planning".into()));
+        assert_eq!(name(slot.then), Some("review".into()));
+        assert_eq!(
+            slot.oneline(now),
+            "write (30m left) | meeting in 1h30m | next: review"
+        );
+    }
+
+    #[test]
+    fn between_events() {
+        let scheduler = scheduler();
+        let now = at(10, 15);
+        let slot = Slot::at(&scheduler, now);
+
+        assert_eq!(name(slot.current), None);
+        assert_eq!(name(slot.task), Some("review".into()));
+        assert_eq!(slot.task.unwrap().start, at(13, 0));
+        assert_eq!(name(slot.then), None);
+        assert_eq!(
+            slot.text(now),
+            "Now:          nothing scheduled\n\
+             Next task:    review at 1:00 pm (1h)\n\
+             Next meeting: 11:00 am (in 45m)\n"
+        );
+    }
+
+    #[test]
+    fn after_the_last_event() {
+        let scheduler = scheduler();
+        let now = at(14, 0) + Duration::minutes(1);
+        let slot = Slot::at(&scheduler, now);
+
+        assert!(slot.current.is_none());
+        assert!(slot.task.is_none());
+        assert!(slot.next_meeting.is_none());
+        assert!(slot.then.is_none());
+        assert_eq!(slot.oneline(now), "nothing scheduled");
+    }
+}
@@ -0,0 +1,366 @@
+use crate::dates;
+use crate::scheduler::{human_time, EventData, Scheduler};
+use chrono::{DateTime, Duration, NaiveDate};
+use chrono_tz::Tz;
+use std::{
+    collections::HashMap,
+    fmt::{Display, Write},
+};
+
+/// How the schedule used the available time, and what didn't fit.
+#[derive(Debug)]
+pub struct Summary {
+    pub days: Vec<Day>,
+    pub unscheduled: Vec<Unscheduled>,
+}
+
+#[derive(Debug)]
+pub struct Day {
+    pub date: NaiveDate,
+    pub free: Duration,
+    pub scheduled: Duration,
+}
+
+#[derive(Debug)]
+pub struct Unscheduled {
+    pub uuid: String,
+    pub description: String,
+    pub remaining: Duration,
+    pub needed: Duration,
+}
+
+impl Summary {
+    pub fn new(scheduler: &Scheduler) -> Self {
+        let mut days = Vec::new();
+
+        let tz = scheduler.start().timezone();
+        let mut date = scheduler.start().date_naive();
+        while dates::midnight(&tz, date) < scheduler.end() {
+            let next = date.succ_opt().expect("dates should not overflow");
+            let from = dates::midnight(&tz, date).max(scheduler.start());
+            let to = dates::midnight(&tz, next).min(scheduler.end());
+
+            days.push(Day {
+                date,
+                free: free_time(scheduler, from, to),
+                scheduled: scheduled_time(scheduler, from, to),
+            });
+
+            date = next;
+        }
+
+        let mut scheduled_by_task: HashMap<&str, Duration> = HashMap::new();
+        for event in &scheduler.commitments {
+            if let EventData::Task { uuid, .. } = &event.what {
+                *scheduled_by_task.entry(uuid).or_insert_with(Duration::zero) += event.duration();
+            }
+        }
+
+        let mut unscheduled: Vec<Unscheduled> = scheduler
+            .tasks()
+            .filter_map(|task| {
+                let remaining = scheduler.remaining_time(&task.uuid)?;
+                if remaining <= Duration::zero() {
+                    return None;
+                }
+
+                let scheduled = scheduled_by_task
+                    .get(task.uuid.as_str())
+                    .copied()
+                    .unwrap_or_else(Duration::zero);
+
+                Some(Unscheduled {
+                    uuid: task.uuid.clone(),
+                    description: task.description.clone(),
+                    remaining,
+                    needed: scheduled + remaining,
+                })
+            })
+            .collect();
+        unscheduled.sort_by(|a, b| b.remaining.cmp(&a.remaining).then(a.uuid.cmp(&b.uuid)));
+
+        Self { days, unscheduled }
+    }
+
+    pub fn free(&self) -> Duration {
+        self.days
+            .iter()
+            .fold(Duration::zero(), |total, day| total + day.free)
+    }
+
+    pub fn scheduled(&self) -> Duration {
+        self.days
+            .iter()
+            .fold(Duration::zero(), |total, day| total + day.scheduled)
+    }
+
+    /// All the work we know about: what got scheduled plus what didn't fit.
+    pub fn estimated(&self) -> Duration {
+        self.unscheduled
+            .iter()
+            .fold(self.scheduled(), |total, task| total + task.remaining)
+    }
+}
+
+impl Display for Summary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("\n########## Summary ##########\n\n")?;
+
+        for day in &self.days {
+            writeln!(
+                f,
+                "{}: {} free, {} scheduled ({})",
+                day.date.format("%b %d"),
+                human_time(day.free),
+                human_time(day.scheduled),
+                percent(day.scheduled, day.free),
+            )?;
+        }
+
+        writeln!(
+            f,
+            "\nOverall: {} free, {} scheduled ({}), {} of work estimated",
+            human_time(self.free()),
+            human_time(self.scheduled()),
+            percent(self.scheduled(), self.free()),
+            human_time(self.estimated()),
+        )?;
+
+        if self.estimated() > self.free() {
+            writeln!(
+                f,
+                "Overcommitted by {}.",
+                human_time(self.estimated() - self.free())
+            )?;
+        }
+
+        if !self.unscheduled.is_empty() {
+            writeln!(f, "\nDidn't fit ({}):", self.unscheduled.len())?;
+
+            for task in &self.unscheduled {
+                let remaining = human_time(task.remaining);
+                let mut pad = 6_usize.saturating_sub(remaining.len());
+
+                f.write_str("  ")?;
+                f.write_str(&remaining)?;
+                while pad > 0 {
+                    f.write_char(' ')?;
+                    pad -= 1;
+                }
+                writeln!(f, " of {} - {}", human_time(task.needed), task.description)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Time between `from` and `to` that isn't blocked off somehow.
+fn free_time(scheduler: &Scheduler, from: DateTime<Tz>, to: DateTime<Tz>) -> Duration {
+    let covered = unavailable(scheduler, from, to)
+        .iter()
+        .fold(Duration::zero(), |total, (start, end)| {
+            total + (*end - *start)
+        });
+
+    Duration::zero().max((to - from) - covered)
+}
+
+/// Time between `from` and `to` spent on tasks, counting only free time.
+/// Fixed tasks can be pinned on top of a meeting, and that time has
+/// already been taken out of the free time, so counting it again would
+/// push utilization past 100%.
+fn scheduled_time(scheduler: &Scheduler, from: DateTime<Tz>, to: DateTime<Tz>) -> Duration {
+    let unavailable = unavailable(scheduler, from, to);
+
+    scheduler
+        .commitments
+        .iter()
+        .filter(|event| matches!(event.what, EventData::Task { .. }))
+        .map(|event| (event.start.max(from), event.end.min(to)))
+        .filter(|(start, end)| start < end)
+        .map(|(start, end)| {
+            unavailable
+                .iter()
+                .map(|(block_start, block_end)| (start.max(*block_start), end.min(*block_end)))
+                .filter(|(overlap_start, overlap_end)| overlap_start < overlap_end)
+                .fold(end - start, |total, (overlap_start, overlap_end)| {
+                    total - (overlap_end - overlap_start)
+                })
+        })
+        .fold(Duration::zero(), |total, duration| total + duration)
+}
+
+/// Blocked-off time between `from` and `to`, sorted and merged so that no
+/// two intervals overlap.
+fn unavailable(
+    scheduler: &Scheduler,
+    from: DateTime<Tz>,
+    to: DateTime<Tz>,
+) -> Vec<(DateTime<Tz>, DateTime<Tz>)> {
+    let mut blocks: Vec<(DateTime<Tz>, DateTime<Tz>)> = scheduler
+        .commitments
+        .iter()
+        .filter(|event| event.what.is_unavailable())
+        .map(|event| (event.start.max(from), event.end.min(to)))
+        .filter(|(start, end)| start < end)
+        .collect();
+    blocks.sort();
+
+    // Blocks can overlap (a meeting during off hours, say) so we need to
+    // merge them before adding them up.
+    let mut merged: Vec<(DateTime<Tz>, DateTime<Tz>)> = Vec::new();
+    for (start, end) in blocks {
+        match merged.last_mut() {
+            Some((_, cur_end)) if start <= *cur_end => *cur_end = (*cur_end).max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    merged
+}
+
+fn percent(part: Duration, whole: Duration) -> String {
+    if whole <= Duration::zero() {
+        return "-".into();
+    }
+
+    format!(
+        "{:.0}%",
+        part.num_seconds() as f64 / whole.num_seconds() as f64 * 100.0
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::calendar::{BlockedTime, Status};
+    use crate::plan::{plan, Inputs};
+    use crate::test_support::TaskBuilder;
+    use chrono::TimeZone;
+    use chrono_tz::America::Chicago;
+
+    fn meeting(start: (u32, u32), end: (u32, u32)) -> BlockedTime {
+        let at = |(h, m)| {
+            Chicago
+                .with_ymd_and_hms(2025, 3, 3, h, m, 0)
+                .unwrap()
+                .to_utc()
+        };
+
+        BlockedTime {
+            start: at(start),
+            end: at(end),
+            status: Status::Busy,
+            title: None,
+        }
+    }
+
+    /// Monday and Tuesday, with meetings on Monday and more work than fits.
+    fn summary() -> Summary {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 0, 0, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(2))
+                .with_busy(vec![
+                    meeting((10, 0), (11, 0)),
+                    // These overlap, so they only take 1h30m together.
+                    meeting((13, 0), (14, 0)),
+                    meeting((13, 30), (14, 30)),
+                    // The 4 minutes left after this are a break, not work.
+                    meeting((17, 0), (17, 26)),
+                ])
+                .with_tasks(vec![
+                    TaskBuilder::new("big")
+                        .with_estimate(Duration::hours(12))
+                        .with_urgency(5.0)
+                        .build(),
+                    TaskBuilder::new("small")
+                        .with_estimate(Duration::hours(3))
+                        .build(),
+                    TaskBuilder::new("later")
+                        .with_estimate(Duration::hours(2))
+                        .with(serde_json::json!({"wait": "20250310T000000Z"}))
+                        .build(),
+                ]),
+        );
+
+        Summary::new(&scheduler)
+    }
+
+    #[test]
+    fn free_time_leaves_out_meetings_but_not_breaks() {
+        let summary = summary();
+
+        let days: Vec<_> = summary
+            .days
+            .iter()
+            .map(|day| (day.date.to_string(), day.free, day.scheduled))
+            .collect();
+
+        assert_eq!(
+            days,
+            vec![
+                (
+                    "2025-03-03".to_string(),
+                    Duration::minutes(5 * 60 + 34),
+                    Duration::minutes(5 * 60 + 30),
+                ),
+                (
+                    "2025-03-04".to_string(),
+                    Duration::minutes(8 * 60 + 30),
+                    Duration::minutes(8 * 60 + 30),
+                ),
+            ]
+        );
+        assert_eq!(summary.free(), Duration::minutes(14 * 60 + 4));
+        assert_eq!(summary.scheduled(), Duration::hours(14));
+    }
+
+    #[test]
+    fn pins_on_meetings_only_count_once() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 3, 0, 0, 0).unwrap();
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(1))
+                .with_busy(vec![meeting((10, 0), (11, 0))])
+                .with_tasks(vec![TaskBuilder::new("standup")
+                    .with_estimate(Duration::hours(1))
+                    .with(serde_json::json!({
+                        "tags": ["fixed"],
+                        "scheduled": "20250303T163000Z",
+                    }))
+                    .build()]),
+        );
+        let summary = Summary::new(&scheduler);
+
+        // The pin runs 10:30 to 11:30, so only the half hour after the
+        // meeting is scheduled work.
+        assert_eq!(summary.free(), Duration::minutes(7 * 60 + 30));
+        assert_eq!(summary.scheduled(), Duration::minutes(30));
+    }
+
+    #[test]
+    fn lists_what_did_not_fit() {
+        let summary = summary();
+
+        let unscheduled: Vec<_> = summary
+            .unscheduled
+            .iter()
+            .map(|task| (task.uuid.as_str(), task.remaining, task.needed))
+            .collect();
+
+        assert_eq!(
+            unscheduled,
+            vec![
+                ("later", Duration::hours(2), Duration::hours(2)),
+                ("small", Duration::hours(1), Duration::hours(3)),
+            ]
+        );
+        assert_eq!(summary.estimated(), Duration::hours(17));
+
+        let out = summary.to_string();
+        assert!(out.contains("Overcommitted by 2h56m."), "{out}");
+        assert!(out.contains("Didn't fit (2):"), "{out}");
+    }
+}
@@ -1,13 +1,18 @@
 use crate::config::Config;
-use chrono::{DateTime, Duration, Utc};
+use crate::dates;
+use chrono::{DateTime, Datelike, Days, Duration, NaiveTime, Utc, Weekday};
+use chrono_tz::Tz;
 use std::collections::HashSet;
 
-#[derive(Debug, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Deserialize)]
 pub struct Task {
     pub uuid: String,
 
     pub description: String,
 
+    #[serde(default)]
+    pub project: Option<String>,
+
     pub urgency: f64,
 
     pub status: Status,
@@ -24,9 +29,43 @@ pub struct Task {
     #[serde(default, deserialize_with = "crate::dates::tw_datetime_opt")]
     pub target: Option<DateTime<Utc>>,
 
+    #[serde(default, deserialize_with = "crate::dates::tw_datetime_opt")]
+    pub scheduled: Option<DateTime<Utc>>,
+
+    /// When the task was started, if it's active right now.
+    #[serde(default, deserialize_with = "crate::dates::tw_datetime_opt")]
+    pub start: Option<DateTime<Utc>>,
+
     #[serde(default, deserialize_with = "crate::dates::duration")]
     pub estimate: Option<Duration>,
 
+    /// How much time has already gone into this task (`spent` UDA.)
+    #[serde(default, deserialize_with = "crate::dates::duration")]
+    pub spent: Option<Duration>,
+
+    /// How long the task would take if everything went well (`optimistic`
+    /// UDA.) Only used for forecasting.
+    #[serde(default, deserialize_with = "crate::dates::duration")]
+    pub optimistic: Option<Duration>,
+
+    /// How long the task would take if everything went wrong (`pessimistic`
+    /// UDA.) Only used for forecasting.
+    #[serde(default, deserialize_with = "crate::dates::duration")]
+    pub pessimistic: Option<Duration>,
+
+    /// The earliest time of day this task can be worked on (`notbefore` UDA.)
+    #[serde(default, deserialize_with = "crate::dates::time_of_day")]
+    pub notbefore: Option<NaiveTime>,
+
+    /// The latest time of day this task can be worked on (`notafter` UDA.)
+    #[serde(default, deserialize_with = "crate::dates::time_of_day")]
+    pub notafter: Option<NaiveTime>,
+
+    /// The days of the week this task can be worked on (`weekdays` UDA.)
+    /// Empty means any day.
+    #[serde(default, deserialize_with = "crate::dates::weekdays")]
+    pub weekdays: HashSet<Weekday>,
+
     #[serde(default)]
     pub depends: HashSet<String>,
 
@@ -35,6 +74,21 @@ pub struct Task {
 }
 
 impl Task {
+    /// Whether this task is in the given project or one of its subprojects
+    /// (so `work.client` is in `work`.)
+    pub fn in_project(&self, project: &str) -> bool {
+        match &self.project {
+            None => false,
+            Some(ours) => {
+                ours == project
+                    || ours
+                        .strip_prefix(project)
+                        .map(|rest| rest.starts_with('.'))
+                        .unwrap_or(false)
+            }
+        }
+    }
+
     pub fn available_at(&self, when: DateTime<Utc>) -> bool {
         match self.wait {
             None => true,
@@ -42,10 +96,90 @@ impl Task {
         }
     }
 
+    /// When this task has to start, if it's a fixed-time commitment. That
+    /// means it's tagged `+fixed` and `scheduled` at a particular time of day
+    /// (as opposed to just a day, which Taskwarrior stores as local
+    /// midnight.)
+    pub fn fixed_start(&self, tz: &Tz) -> Option<DateTime<Utc>> {
+        if !self.tags.contains("fixed") {
+            return None;
+        }
+
+        self.scheduled
+            .filter(|scheduled| scheduled.with_timezone(tz).time() != NaiveTime::MIN)
+    }
+
+    /// Whether `when` falls inside the task's own availability (weekdays and
+    /// time of day.) This is separate from `wait`, which only says when the
+    /// task first becomes available. A `notafter` earlier than `notbefore`
+    /// means the task runs overnight, like `22:00` to `02:00`. The hours
+    /// after midnight belong to the day the session started, so with
+    /// `weekdays:mon` that runs from Monday night into Tuesday morning.
+    pub fn allowed_at(&self, when: &DateTime<Tz>) -> bool {
+        let time = when.time();
+
+        let (time_allowed, day) = match (self.notbefore, self.notafter) {
+            (Some(start), Some(end)) if end < start && time < end => (true, when.weekday().pred()),
+            (Some(start), Some(end)) if end < start => (start <= time, when.weekday()),
+            (start, end) => (
+                start.map(|start| start <= time).unwrap_or(true)
+                    && end.map(|end| time < end).unwrap_or(true),
+                when.weekday(),
+            ),
+        };
+
+        (self.weekdays.is_empty() || self.weekdays.contains(&day)) && time_allowed
+    }
+
+    /// How long the task can be worked on starting at `when` before it runs
+    /// into `notafter`, or `None` if it doesn't have a limit.
+    pub fn allowed_for(&self, when: &DateTime<Tz>) -> Option<Duration> {
+        let end = self.notafter?;
+        let time = when.time();
+
+        if end < time
+            && self
+                .notbefore
+                .is_some_and(|start| end < start && start <= time)
+        {
+            // Overnight, so `notafter` is tomorrow.
+            Some(Duration::days(1) - (time - end))
+        } else {
+            Some(Duration::zero().max(end - time))
+        }
+    }
+
+    /// The first time after `after` that [`Task::allowed_at`] becomes true,
+    /// or `None` if it never does.
+    pub fn next_allowed(&self, after: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = after.timezone();
+        let today = after.date_naive();
+
+        // Availability only changes at midnight (for weekdays) or at
+        // `notbefore`, and repeats every week.
+        (0..=7)
+            .filter_map(|days| today.checked_add_days(Days::new(days)))
+            .flat_map(|date| {
+                [NaiveTime::MIN]
+                    .into_iter()
+                    .chain(self.notbefore)
+                    .map(move |time| dates::at_local(&tz, date, time))
+            })
+            .find(|when| when > after && self.allowed_at(when))
+    }
+
     pub fn urgency_at(&self, when: DateTime<Utc>, config: &Config) -> f64 {
-        self.urgency
-            + self.base_due_urgency_at(when) * config.urgency_due_coefficient
-            + self.base_age_urgency_at(when, config) * config.urgency_age_coefficient
+        self.urgency_breakdown_at(when, config).total()
+    }
+
+    pub fn urgency_breakdown_at(&self, when: DateTime<Utc>, config: &Config) -> Urgency {
+        Urgency {
+            base: self.urgency,
+            due: self.base_due_urgency_at(when),
+            due_coefficient: config.urgency_due_coefficient,
+            age: self.base_age_urgency_at(when, config),
+            age_coefficient: config.urgency_age_coefficient,
+        }
     }
 
     fn base_due_urgency_at(&self, when: DateTime<Utc>) -> f64 {
@@ -98,7 +232,29 @@ impl Task {
     }
 }
 
-#[derive(Debug, serde::Deserialize, PartialEq, Eq)]
+/// The parts that go into a task's urgency at some point in time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Urgency {
+    /// The urgency Taskwarrior calculated, minus the terms we calculate
+    /// ourselves (see `Cli::run`.)
+    pub base: f64,
+
+    /// How close the due (or target) date is, from 0.2 to 1.0.
+    pub due: f64,
+    pub due_coefficient: f64,
+
+    /// How old the task is, from 0.0 to 1.0.
+    pub age: f64,
+    pub age_coefficient: f64,
+}
+
+impl Urgency {
+    pub fn total(&self) -> f64 {
+        self.base + self.due * self.due_coefficient + self.age * self.age_coefficient
+    }
+}
+
+#[derive(Debug, Clone, serde::Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum Status {
     Pending,
@@ -106,3 +262,92 @@ pub enum Status {
     Deleted,
     Recurring,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_support::TaskBuilder;
+    use chrono::TimeZone;
+    use chrono_tz::America::Chicago;
+
+    /// A time in the first week of March 2025, which started on a Saturday.
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Tz> {
+        Chicago
+            .with_ymd_and_hms(2025, 3, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn allowed_between_notbefore_and_notafter() {
+        let task = TaskBuilder::new("abc")
+            .with(serde_json::json!({"notbefore": "14:00", "notafter": "16:00"}))
+            .build();
+
+        assert!(!task.allowed_at(&at(3, 13, 59)));
+        assert!(task.allowed_at(&at(3, 14, 0)));
+        assert!(!task.allowed_at(&at(3, 16, 0)));
+        assert_eq!(task.allowed_for(&at(3, 15, 0)), Some(Duration::hours(1)));
+        assert_eq!(task.allowed_for(&at(3, 17, 0)), Some(Duration::zero()));
+    }
+
+    #[test]
+    fn allowed_overnight() {
+        let task = TaskBuilder::new("abc")
+            .with(serde_json::json!({"notbefore": "22:00", "notafter": "02:00"}))
+            .build();
+
+        assert!(task.allowed_at(&at(3, 23, 0)));
+        assert!(task.allowed_at(&at(4, 1, 0)));
+        assert!(!task.allowed_at(&at(4, 2, 0)));
+        assert!(!task.allowed_at(&at(4, 12, 0)));
+        assert_eq!(task.allowed_for(&at(3, 23, 0)), Some(Duration::hours(3)));
+        assert_eq!(task.allowed_for(&at(4, 1, 30)), Some(Duration::minutes(30)));
+    }
+
+    #[test]
+    fn overnight_sessions_belong_to_the_day_they_start() {
+        let task = TaskBuilder::new("abc")
+            .with(serde_json::json!({
+                "notbefore": "22:00",
+                "notafter": "02:00",
+                "weekdays": "mon",
+            }))
+            .build();
+
+        // Sunday night runs into Monday morning, so it's not allowed.
+        assert!(!task.allowed_at(&at(3, 1, 0)));
+        assert!(task.allowed_at(&at(3, 23, 0)));
+        assert!(task.allowed_at(&at(4, 1, 0)));
+        assert!(!task.allowed_at(&at(4, 23, 0)));
+        assert_eq!(task.next_allowed(&at(2, 12, 0)), Some(at(3, 22, 0)));
+    }
+
+    #[test]
+    fn allowed_on_weekdays() {
+        let task = TaskBuilder::new("abc")
+            .with(serde_json::json!({"weekdays": "tue,thu"}))
+            .build();
+
+        assert!(!task.allowed_at(&at(3, 10, 0)));
+        assert!(task.allowed_at(&at(4, 10, 0)));
+        assert_eq!(task.allowed_for(&at(4, 10, 0)), None);
+    }
+
+    #[test]
+    fn next_allowed_time() {
+        let afternoons = TaskBuilder::new("abc")
+            .with(serde_json::json!({"notbefore": "14:00"}))
+            .build();
+        let thursdays = TaskBuilder::new("abc")
+            .with(serde_json::json!({"weekdays": "thu", "notbefore": "09:00"}))
+            .build();
+        let overnight = TaskBuilder::new("abc")
+            .with(serde_json::json!({"notbefore": "22:00", "notafter": "02:00"}))
+            .build();
+
+        assert_eq!(afternoons.next_allowed(&at(3, 9, 0)), Some(at(3, 14, 0)));
+        assert_eq!(afternoons.next_allowed(&at(3, 15, 0)), Some(at(4, 14, 0)));
+        assert_eq!(thursdays.next_allowed(&at(3, 9, 0)), Some(at(6, 9, 0)));
+        assert_eq!(overnight.next_allowed(&at(3, 12, 0)), Some(at(3, 22, 0)));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{config::Config, task::Task};
-use color_eyre::eyre::{Context, Result};
+use color_eyre::eyre::{eyre, Context, Result};
 use std::collections::HashMap;
 use tokio::process::Command;
 
@@ -39,6 +39,35 @@ impl Taskwarrior {
 
         Config::parse(&config_text).wrap_err("could not parse config")
     }
+
+    /// Change a task, with the same arguments `task <uuid> modify` takes
+    /// (like `estimate:PT1H` or `+fixed`.)
+    #[tracing::instrument]
+    pub async fn modify(&self, uuid: &str, changes: &[String]) -> Result<()> {
+        let mut command = Command::new(&self.binary);
+        command
+            .arg("rc.confirmation=off")
+            .arg("rc.recurrence.confirmation=no")
+            .arg(uuid)
+            .arg("modify")
+            .args(changes);
+
+        tracing::trace!(?command, "modifying task");
+
+        let output = command
+            .output()
+            .await
+            .wrap_err("could not call Taskwarrior")?;
+
+        if !output.status.success() {
+            return Err(eyre!(
+                "Taskwarrior could not modify {uuid}: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+
+        Ok(())
+    }
 }
 
 pub struct ExportBuilder {
@@ -0,0 +1,67 @@
+use crate::task::Task;
+use chrono::Duration;
+use serde_json::{json, Map, Value};
+
+/// Tasks for tests, built from the same JSON `task export` produces so they
+/// go through the same parsing real tasks do.
+pub struct TaskBuilder {
+    json: Map<String, Value>,
+    estimate: Option<Duration>,
+}
+
+impl TaskBuilder {
+    /// A pending task described by its UUID, with urgency 1 and no estimate.
+    pub fn new(uuid: &str) -> Self {
+        let json = json!({
+            "uuid": uuid,
+            "description": uuid,
+            "urgency": 1.0,
+            "status": "pending",
+            "entry": "20250101T000000Z",
+        });
+
+        Self {
+            json: json.as_object().cloned().unwrap_or_default(),
+            estimate: None,
+        }
+    }
+
+    pub fn with_description(self, description: &str) -> Self {
+        self.with(json!({ "description": description }))
+    }
+
+    pub fn with_urgency(self, urgency: f64) -> Self {
+        self.with(json!({ "urgency": urgency }))
+    }
+
+    pub fn with_project(self, project: &str) -> Self {
+        self.with(json!({ "project": project }))
+    }
+
+    pub fn with_estimate(mut self, estimate: Duration) -> Self {
+        self.estimate = Some(estimate);
+
+        self
+    }
+
+    /// Any other fields, in the format Taskwarrior exports them (like
+    /// `{"wait": "20250310T000000Z"}` or `{"notbefore": "14:00"}`.) These
+    /// replace whatever was there before.
+    pub fn with(mut self, extra: Value) -> Self {
+        if let Value::Object(extra) = extra {
+            self.json.extend(extra);
+        }
+
+        self
+    }
+
+    pub fn build(self) -> Task {
+        let mut task: Task =
+            serde_json::from_value(Value::Object(self.json)).expect("test task should parse");
+        if self.estimate.is_some() {
+            task.estimate = self.estimate;
+        }
+
+        task
+    }
+}
@@ -0,0 +1,265 @@
+use crate::task::Task;
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use color_eyre::eyre::{eyre, Context, Result};
+use std::path::{Path, PathBuf};
+
+/// A span of tracked time from Timewarrior.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interval {
+    pub start: DateTime<Utc>,
+
+    /// `None` if the interval is still being tracked.
+    pub end: Option<DateTime<Utc>>,
+
+    pub tags: Vec<String>,
+}
+
+impl Interval {
+    pub fn duration(&self, now: DateTime<Utc>) -> Duration {
+        Duration::zero().max(self.end.unwrap_or(now) - self.start)
+    }
+
+    /// Whether this interval was tracked against the given task. We match on
+    /// the task's UUID (full or short) or its description, since the usual
+    /// on-modify hook tags intervals with the description.
+    pub fn is_for(&self, task: &Task) -> bool {
+        self.tags.iter().any(|tag| {
+            *tag == task.uuid || *tag == task.description || Some(tag.as_str()) == short_uuid(task)
+        })
+    }
+
+    pub fn parse(line: &str) -> Result<Self> {
+        let line = line
+            .trim()
+            .strip_prefix("inc ")
+            .ok_or(eyre!("Expected interval to start with `inc`"))?;
+
+        let (range, tags) = match line.split_once(" # ") {
+            Some((range, tags)) => (range, parse_tags(tags)),
+            None => (line.trim_end_matches(" #"), Vec::new()),
+        };
+
+        let (start, end) = match range.split_once(" - ") {
+            Some((start, end)) => (start, Some(end)),
+            None => (range, None),
+        };
+
+        Ok(Self {
+            start: parse_datetime(start)?,
+            end: end.map(parse_datetime).transpose()?,
+            tags,
+        })
+    }
+}
+
+fn short_uuid(task: &Task) -> Option<&str> {
+    task.uuid.get(..8)
+}
+
+fn parse_datetime(input: &str) -> Result<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(input.trim(), "%Y%m%dT%H%M%SZ")
+        .map(|naive| naive.and_utc())
+        .wrap_err_with(|| format!("could not parse `{input}` as a Timewarrior date"))
+}
+
+/// Tags are separated by spaces, and quoted if they contain spaces
+/// themselves.
+fn parse_tags(input: &str) -> Vec<String> {
+    let mut tags = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = input.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if quoted => {
+                if let Some(escaped) = chars.next() {
+                    current.push(escaped)
+                }
+            }
+            '"' => quoted = !quoted,
+            ' ' if !quoted => {
+                if !current.is_empty() {
+                    tags.push(std::mem::take(&mut current))
+                }
+            }
+            _ => current.push(c),
+        }
+    }
+
+    if !current.is_empty() {
+        tags.push(current)
+    }
+
+    tags
+}
+
+#[derive(Debug)]
+pub struct Timewarrior {
+    data: PathBuf,
+}
+
+impl Timewarrior {
+    pub fn new(db: Option<PathBuf>) -> Result<Self> {
+        let db = match db {
+            Some(db) => db,
+            None => default_db().ok_or(eyre!(
+                "Could not find a Timewarrior database. Set TIMEWARRIORDB or pass --timewarrior-db."
+            ))?,
+        };
+
+        Ok(Self {
+            data: db.join("data"),
+        })
+    }
+
+    /// Read every interval Timewarrior has recorded.
+    #[tracing::instrument]
+    pub async fn intervals(&self) -> Result<Vec<Interval>> {
+        let mut entries = tokio::fs::read_dir(&self.data)
+            .await
+            .wrap_err_with(|| format!("could not read {}", self.data.display()))?;
+
+        let mut out = Vec::new();
+
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .wrap_err("could not list Timewarrior data files")?
+        {
+            let path = entry.path();
+            if !is_month(&path) {
+                continue;
+            }
+
+            tracing::trace!(?path, "reading Timewarrior data");
+
+            let contents = tokio::fs::read_to_string(&path)
+                .await
+                .wrap_err_with(|| format!("could not read {}", path.display()))?;
+
+            for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+                out.push(
+                    Interval::parse(line)
+                        .wrap_err_with(|| format!("could not parse line in {}", path.display()))?,
+                );
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+/// Whether this is one of the files intervals are kept in, like
+/// `2025-01.data`. The same directory has other `.data` files (`tags.data`,
+/// `undo.data`) in other formats.
+fn is_month(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some("data")
+        && path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| {
+                NaiveDate::parse_from_str(&format!("{stem}-01"), "%Y-%m-%d").is_ok()
+            })
+}
+
+fn default_db() -> Option<PathBuf> {
+    if let Some(db) = std::env::var_os("TIMEWARRIORDB") {
+        return Some(db.into());
+    }
+
+    let home = PathBuf::from(std::env::var_os("HOME")?);
+
+    let legacy = home.join(".timewarrior");
+    if legacy.exists() {
+        return Some(legacy);
+    }
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".local/share"));
+
+    Some(data_home.join("timewarrior")).filter(|db| Path::exists(db))
+}
+
+/// Total time tracked against a task.
+pub fn time_spent(intervals: &[Interval], task: &Task, now: DateTime<Utc>) -> Duration {
+    intervals
+        .iter()
+        .filter(|interval| interval.is_for(task))
+        .fold(Duration::zero(), |total, interval| {
+            total + interval.duration(now)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn parse_closed_interval() {
+        assert_eq!(
+            Interval::parse("inc 20250101T100000Z - 20250101T113000Z # work \"write report\"")
+                .unwrap(),
+            Interval {
+                start: Utc.with_ymd_and_hms(2025, 1, 1, 10, 0, 0).unwrap(),
+                end: Some(Utc.with_ymd_and_hms(2025, 1, 1, 11, 30, 0).unwrap()),
+                tags: vec!["work".into(), "write report".into()],
+            }
+        )
+    }
+
+    #[test]
+    fn parse_open_interval() {
+        let interval = Interval::parse("inc 20250101T100000Z # work").unwrap();
+
+        assert_eq!(interval.end, None);
+        assert_eq!(interval.tags, vec!["work".to_string()]);
+    }
+
+    #[test]
+    fn parse_untagged_interval() {
+        let interval = Interval::parse("inc 20250101T100000Z - 20250101T113000Z").unwrap();
+
+        assert!(interval.tags.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reads_only_interval_files() {
+        let db = std::env::temp_dir().join(format!("task-sched-timew-{}", std::process::id()));
+        let data = db.join("data");
+        let _ = std::fs::remove_dir_all(&db);
+        std::fs::create_dir_all(&data).unwrap();
+
+        std::fs::write(
+            data.join("2025-01.data"),
+            "inc 20250101T100000Z - 20250101T113000Z # work\n",
+        )
+        .unwrap();
+        std::fs::write(data.join("tags.data"), r#"{"work":{"count":1}}"#).unwrap();
+        std::fs::write(
+            data.join("undo.data"),
+            "txn:\n  type: interval\n  before: \n  after: inc 20250101T100000Z # work\n",
+        )
+        .unwrap();
+
+        let intervals = Timewarrior::new(Some(db.clone()))
+            .unwrap()
+            .intervals()
+            .await
+            .unwrap();
+        std::fs::remove_dir_all(&db).unwrap();
+
+        assert_eq!(intervals.len(), 1);
+        assert_eq!(intervals[0].tags, vec!["work".to_string()]);
+    }
+
+    #[test]
+    fn parse_escaped_quote() {
+        assert_eq!(
+            parse_tags(r#""say \"hi\"" other"#),
+            vec![r#"say "hi""#.to_string(), "other".to_string()]
+        )
+    }
+}
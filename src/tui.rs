@@ -0,0 +1,551 @@
+use crate::Cli;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Tz;
+use color_eyre::eyre::{Context, Result};
+use ratatui::{
+    crossterm::event::{self, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Style, Stylize},
+    text::Line,
+    widgets::{Block, List, ListItem, ListState, Paragraph},
+    DefaultTerminal, Frame,
+};
+use std::collections::BTreeMap;
+use task_sched::dates;
+use task_sched::plan::{plan, Inputs};
+use task_sched::scheduler::{human_time, Event, EventData, Scheduler};
+use task_sched::task::Task;
+use task_sched::taskwarrior::Taskwarrior;
+
+/// How much `+` and `-` change an estimate by.
+const ESTIMATE_STEP: i64 = 15;
+
+pub async fn run(cli: &Cli) -> Result<()> {
+    let start = cli.start();
+    let end = cli.end(start);
+    let inputs = cli.inputs(start, end).await?;
+
+    let mut app = App::new(cli, start, inputs);
+
+    let mut terminal = ratatui::init();
+    let result = app.run(&mut terminal).await;
+    ratatui::restore();
+
+    result
+}
+
+/// Changes to a task that haven't been written to Taskwarrior yet.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Override {
+    pin: Option<Pin>,
+    skip_until: Option<DateTime<Utc>>,
+    estimate: Option<Duration>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Pin {
+    At(DateTime<Utc>),
+    Unpinned,
+}
+
+impl Override {
+    fn apply(&self, task: &mut Task) {
+        match self.pin {
+            Some(Pin::At(start)) => {
+                task.scheduled = Some(start);
+                task.tags.insert("fixed".into());
+            }
+            Some(Pin::Unpinned) => {
+                task.tags.remove("fixed");
+            }
+            None => (),
+        }
+
+        if let Some(until) = self.skip_until {
+            task.wait = Some(task.wait.map_or(until, |wait| wait.max(until)));
+        }
+
+        if let Some(estimate) = self.estimate {
+            task.estimate = Some(estimate);
+        }
+    }
+
+    /// Arguments for `task modify` that make the same change.
+    fn changes(&self) -> Vec<String> {
+        let mut out = Vec::new();
+
+        match self.pin {
+            Some(Pin::At(start)) => {
+                out.push(format!("scheduled:{}", start.format("%Y-%m-%dT%H:%M:%SZ")));
+                out.push("+fixed".into());
+            }
+            Some(Pin::Unpinned) => out.push("-fixed".into()),
+            None => (),
+        }
+
+        if let Some(until) = self.skip_until {
+            out.push(format!("wait:{}", until.format("%Y-%m-%dT%H:%M:%SZ")));
+        }
+
+        if let Some(estimate) = self.estimate {
+            out.push(format!("estimate:PT{}M", estimate.num_minutes()));
+        }
+
+        out
+    }
+}
+
+struct App<'a> {
+    cli: &'a Cli,
+    start: DateTime<Tz>,
+    inputs: Inputs,
+    overrides: BTreeMap<String, Override>,
+    scheduler: Scheduler,
+    days: Vec<NaiveDate>,
+    day: usize,
+    selected: usize,
+    message: Option<String>,
+}
+
+impl<'a> App<'a> {
+    fn new(cli: &'a Cli, start: DateTime<Tz>, inputs: Inputs) -> Self {
+        let scheduler = plan(inputs.clone());
+
+        let mut days: Vec<NaiveDate> = scheduler
+            .commitments
+            .iter()
+            .map(|event| event.start.date_naive())
+            .collect();
+        days.dedup();
+
+        Self {
+            cli,
+            start,
+            inputs,
+            overrides: BTreeMap::new(),
+            scheduler,
+            days,
+            day: 0,
+            selected: 0,
+            message: None,
+        }
+    }
+
+    async fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        loop {
+            terminal
+                .draw(|frame| self.draw(frame))
+                .wrap_err("could not draw")?;
+
+            let event = tokio::task::spawn_blocking(event::read)
+                .await?
+                .wrap_err("could not read from the terminal")?;
+
+            let event::Event::Key(key) = event else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+
+            self.message = None;
+
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Left | KeyCode::Char('h') => self.move_day(-1),
+                KeyCode::Right | KeyCode::Char('l') => self.move_day(1),
+                KeyCode::Up | KeyCode::Char('k') => self.selected = self.selected.saturating_sub(1),
+                KeyCode::Down | KeyCode::Char('j') => {
+                    self.selected = (self.selected + 1).min(self.events().len().saturating_sub(1))
+                }
+                KeyCode::Char('p') => self.toggle_pin(),
+                KeyCode::Char('s') => self.toggle_skip(),
+                KeyCode::Char('+') | KeyCode::Char('=') => self.adjust_estimate(ESTIMATE_STEP),
+                KeyCode::Char('-') => self.adjust_estimate(-ESTIMATE_STEP),
+                KeyCode::Char('w') => self.write_back().await,
+                _ => (),
+            }
+        }
+    }
+
+    /// Everything scheduled on the day being shown.
+    fn events(&self) -> Vec<&Event> {
+        let Some(day) = self.days.get(self.day) else {
+            return Vec::new();
+        };
+
+        self.scheduler
+            .commitments
+            .iter()
+            .filter(|event| event.start.date_naive() == *day)
+            .collect()
+    }
+
+    fn move_day(&mut self, by: isize) {
+        self.day = self
+            .day
+            .saturating_add_signed(by)
+            .min(self.days.len().saturating_sub(1));
+        self.selected = 0;
+    }
+
+    /// The task under the cursor, and where it's scheduled.
+    fn selected_task(&self) -> Option<(String, DateTime<Tz>)> {
+        let event = self.events().get(self.selected).copied()?;
+
+        match &event.what {
+            EventData::Task { uuid, .. } => Some((uuid.clone(), event.start)),
+            _ => None,
+        }
+    }
+
+    /// The task as it would be with our changes, before scheduling.
+    fn task(&self, uuid: &str) -> Option<Task> {
+        let mut task = self
+            .inputs
+            .tasks
+            .iter()
+            .find(|task| task.uuid == uuid)?
+            .clone();
+
+        if let Some(change) = self.overrides.get(uuid) {
+            change.apply(&mut task);
+        }
+
+        Some(task)
+    }
+
+    fn change(&mut self, edit: impl FnOnce(&mut Override, &Task)) {
+        let Some((uuid, _)) = self.selected_task() else {
+            self.message = Some("Select a task first".into());
+            return;
+        };
+        let Some(task) = self.task(&uuid) else {
+            return;
+        };
+
+        let change = self.overrides.entry(uuid.clone()).or_default();
+        edit(change, &task);
+        if *change == Override::default() {
+            self.overrides.remove(&uuid);
+        }
+
+        self.replan(&uuid);
+    }
+
+    fn toggle_pin(&mut self) {
+        let Some((uuid, slot)) = self.selected_task() else {
+            self.message = Some("Select a task first".into());
+            return;
+        };
+
+        let tz = self.start.timezone();
+        self.change(|change, task| {
+            change.pin = match (change.pin, task.fixed_start(&tz)) {
+                // undoing a pin we made here
+                (Some(Pin::At(_)), _) => None,
+                (_, Some(_)) => Some(Pin::Unpinned),
+                (Some(Pin::Unpinned), None) => None,
+                (None, None) => Some(Pin::At(slot.to_utc())),
+            }
+        });
+
+        // Logs don't show up in here, so the scheduler's warning about
+        // double-booking wouldn't either.
+        if let Some(warning) = collision_warning(&self.scheduler, &uuid) {
+            self.message = Some(warning);
+        }
+    }
+
+    fn toggle_skip(&mut self) {
+        let Some(tomorrow) = self
+            .start
+            .date_naive()
+            .succ_opt()
+            .map(|date| dates::midnight(&self.start.timezone(), date))
+        else {
+            return;
+        };
+
+        self.change(|change, _| {
+            change.skip_until = match change.skip_until {
+                Some(_) => None,
+                None => Some(tomorrow.to_utc()),
+            }
+        })
+    }
+
+    fn adjust_estimate(&mut self, minutes: i64) {
+        self.change(|change, task| {
+            let current = task.estimate.unwrap_or(Duration::minutes(10));
+            change.estimate =
+                Some(Duration::minutes(ESTIMATE_STEP).max(current + Duration::minutes(minutes)))
+        })
+    }
+
+    fn replan(&mut self, keep: &str) {
+        let mut inputs = self.inputs.clone();
+        for task in &mut inputs.tasks {
+            if let Some(change) = self.overrides.get(&task.uuid) {
+                change.apply(task);
+            }
+        }
+
+        self.scheduler = plan(inputs);
+
+        // Follow the task we just changed, if it's still on this day.
+        let events = self.events();
+        self.selected = events
+            .iter()
+            .position(|event| matches!(&event.what, EventData::Task { uuid, .. } if uuid == keep))
+            .unwrap_or(self.selected)
+            .min(events.len().saturating_sub(1));
+    }
+
+    async fn write_back(&mut self) {
+        if self.overrides.is_empty() {
+            self.message = Some("Nothing to write".into());
+            return;
+        }
+
+        let tw = Taskwarrior::new(self.cli.taskwarrior_binary.clone());
+        let mut written = 0;
+
+        while let Some((uuid, change)) = self.overrides.pop_first() {
+            if let Err(err) = tw.modify(&uuid, &change.changes()).await {
+                self.message = Some(format!("{err:#}"));
+                self.overrides.insert(uuid, change);
+                break;
+            }
+
+            // Taskwarrior has the change now, so it's part of the inputs
+            // rather than something to undo.
+            if let Some(task) = self.inputs.tasks.iter_mut().find(|task| task.uuid == uuid) {
+                change.apply(task);
+            }
+            written += 1;
+        }
+
+        if self.message.is_none() {
+            self.message = Some(format!("Wrote changes to {written} task(s)"));
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [header, body, details, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Length(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let title = match self.days.get(self.day) {
+            Some(day) => format!(
+                " {} ({}/{})",
+                day.format("%a %b %-d"),
+                self.day + 1,
+                self.days.len()
+            ),
+            None => " Nothing scheduled".into(),
+        };
+        frame.render_widget(Line::from(title).bold(), header);
+
+        let items: Vec<ListItem> = self
+            .events()
+            .into_iter()
+            .map(|event| self.item(event))
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered())
+            .highlight_style(Style::new().reversed())
+            .highlight_symbol("> ");
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, body, &mut state);
+
+        frame.render_widget(Paragraph::new(self.details()), details);
+
+        let help = match &self.message {
+            Some(message) => message.clone(),
+            None => format!(
+                "←/→ day  ↑/↓ select  p pin  s skip today  +/- estimate  w write ({} unsaved)  q quit",
+                self.overrides.len()
+            ),
+        };
+        frame.render_widget(Line::from(help).dim(), footer);
+    }
+
+    fn item(&self, event: &Event) -> ListItem<'static> {
+        let times = format!(
+            "{:>8} - {:<8} {:>6}  ",
+            event.start.format("%-I:%M %P").to_string(),
+            event.end.format("%-I:%M %P").to_string(),
+            human_time(event.duration()),
+        );
+
+        let line = match &event.what {
+            EventData::Task {
+                uuid,
+                name,
+                is_meta,
+            } => {
+                let mut label = String::new();
+                if self.overrides.contains_key(uuid) {
+                    label.push_str("* ");
+                }
+                if self
+                    .task(uuid)
+                    .is_some_and(|task| task.fixed_start(&self.start.timezone()).is_some())
+                {
+                    label.push_str("[pinned] ");
+                }
+                if *is_meta {
+                    label.push_str("META - ");
+                }
+                label.push_str(name);
+
+                Line::from(times + &label)
+            }
+            EventData::Blocked => Line::from(times + "busy").red(),
+            EventData::Meeting { title } => Line::from(times + title).red(),
+            EventData::OffHours => Line::from(times + "off").dark_gray(),
+            EventData::Buffer => Line::from(times + "buffer").dark_gray(),
+            EventData::Break => Line::from(times + "break").dark_gray(),
+        };
+
+        ListItem::new(line)
+    }
+
+    fn details(&self) -> Vec<Line<'static>> {
+        let Some(task) = self.selected_task().and_then(|(uuid, _)| self.task(&uuid)) else {
+            return Vec::new();
+        };
+
+        let estimate = task.estimate.unwrap_or(Duration::minutes(10));
+        let mut out = vec![Line::from(task.description.clone()).bold()];
+
+        let mut facts = format!("estimate {}", human_time(estimate));
+        if let Some(remaining) = self
+            .scheduler
+            .remaining_time(&task.uuid)
+            .filter(|remaining| *remaining > Duration::zero())
+        {
+            facts.push_str(&format!(", {} didn't fit", human_time(remaining)));
+        }
+        if let Some(project) = &task.project {
+            facts.push_str(&format!(", project {project}"));
+        }
+        if let Some(change) = self.overrides.get(&task.uuid) {
+            facts.push_str(&format!(", unsaved: {}", change.changes().join(" ")));
+        }
+        out.push(Line::from(facts));
+
+        out
+    }
+}
+
+/// What a pinned task was pinned on top of, if anything.
+fn collision_warning(scheduler: &Scheduler, uuid: &str) -> Option<String> {
+    let busy: Vec<String> = scheduler
+        .collisions()
+        .into_iter()
+        .filter(
+            |(pinned, _)| matches!(&pinned.what, EventData::Task { uuid: id, .. } if id == uuid),
+        )
+        .map(|(_, busy)| match &busy.what {
+            EventData::Meeting { title } => {
+                format!("{title} at {}", busy.start.format("%-I:%M %P"))
+            }
+            _ => format!("busy time at {}", busy.start.format("%-I:%M %P")),
+        })
+        .collect();
+
+    if busy.is_empty() {
+        None
+    } else {
+        Some(format!("Pinned on top of {}", busy.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::America::Chicago;
+    use task_sched::calendar::{BlockedTime, Status};
+    use task_sched::test_support::TaskBuilder;
+
+    #[test]
+    fn pinning_makes_a_fixed_task() {
+        let at = Utc.with_ymd_and_hms(2025, 3, 4, 15, 0, 0).unwrap();
+        let change = Override {
+            pin: Some(Pin::At(at)),
+            ..Override::default()
+        };
+
+        let mut task = TaskBuilder::new("abc")
+            .with_estimate(Duration::hours(1))
+            .build();
+        change.apply(&mut task);
+
+        assert_eq!(task.fixed_start(&Tz::UTC), Some(at));
+        assert_eq!(
+            change.changes(),
+            vec!["scheduled:2025-03-04T15:00:00Z", "+fixed"]
+        );
+    }
+
+    #[test]
+    fn skipping_waits_until_later() {
+        let until = Utc.with_ymd_and_hms(2025, 3, 5, 6, 0, 0).unwrap();
+        let change = Override {
+            skip_until: Some(until),
+            estimate: Some(Duration::minutes(90)),
+            ..Override::default()
+        };
+
+        let mut task = TaskBuilder::new("abc")
+            .with_estimate(Duration::hours(1))
+            .build();
+        change.apply(&mut task);
+
+        assert!(!task.available_at(until - Duration::minutes(1)));
+        assert_eq!(task.estimate, Some(Duration::minutes(90)));
+        assert_eq!(
+            change.changes(),
+            vec!["wait:2025-03-05T06:00:00Z", "estimate:PT90M"]
+        );
+    }
+
+    #[test]
+    fn pinning_over_a_meeting_warns() {
+        let start = Chicago.with_ymd_and_hms(2025, 3, 4, 9, 0, 0).unwrap();
+        let meeting = |hour, title: Option<&str>| BlockedTime {
+            start: (start + Duration::hours(hour)).to_utc(),
+            end: (start + Duration::hours(hour + 1)).to_utc(),
+            status: Status::Busy,
+            title: title.map(String::from),
+        };
+
+        let mut task = TaskBuilder::new("abc")
+            .with_estimate(Duration::hours(1))
+            .build();
+        Override {
+            pin: Some(Pin::At((start + Duration::minutes(90)).to_utc())),
+            ..Override::default()
+        }
+        .apply(&mut task);
+
+        let scheduler = plan(
+            Inputs::new(start, start + Duration::days(1))
+                .with_busy(vec![meeting(1, Some("Standup")), meeting(2, None)])
+                .with_tasks(vec![task]),
+        );
+
+        assert_eq!(
+            collision_warning(&scheduler, "abc").as_deref(),
+            Some("Pinned on top of Standup at 10:00 am, busy time at 11:00 am")
+        );
+        assert_eq!(collision_warning(&scheduler, "other"), None);
+    }
+}
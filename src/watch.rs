@@ -0,0 +1,228 @@
+use crate::{print_plan, Cli};
+use chrono::{DateTime, Duration};
+use chrono_tz::Tz;
+use color_eyre::eyre::{Context, Result};
+use std::{
+    path::{Path, PathBuf},
+    time::{Instant, SystemTime},
+};
+use task_sched::calendar::BlockedTime;
+use task_sched::scheduler::Event;
+use task_sched::taskwarrior::Taskwarrior;
+use task_sched::{dates, diff, ics, plan};
+
+#[derive(Clone, clap::Args)]
+pub struct Watch {
+    /// How often to check Taskwarrior's data directory for changes.
+    #[clap(long, value_parser = dates::parse_duration, default_value = "5s")]
+    check_every: Duration,
+
+    /// How often to fetch calendar events again.
+    #[clap(long, value_parser = dates::parse_duration, default_value = "5m")]
+    calendar_every: Duration,
+
+    /// Write the schedule here as JSON whenever it changes.
+    #[clap(long)]
+    output_json: Option<PathBuf>,
+
+    /// Write scheduled tasks here as an iCalendar file whenever the schedule
+    /// changes.
+    #[clap(long)]
+    output_ics: Option<PathBuf>,
+}
+
+/// Everything we know from the last time around the loop.
+#[derive(Default)]
+struct State {
+    busy: Vec<BlockedTime>,
+    calendar_fetched: Option<(Instant, DateTime<Tz>)>,
+    data: Option<Vec<(PathBuf, SystemTime, u64)>>,
+    plan: Option<Vec<Event>>,
+}
+
+impl Watch {
+    pub async fn run(&self, cli: &Cli) -> Result<()> {
+        let config = Taskwarrior::new(cli.taskwarrior_binary.clone())
+            .config()
+            .await
+            .wrap_err("could not get config")?;
+
+        let data_dir = config.data_location.unwrap_or_else(|| {
+            PathBuf::from(std::env::var_os("HOME").unwrap_or_default()).join(".task")
+        });
+        tracing::info!(?data_dir, "watching Taskwarrior data");
+
+        let mut state = State::default();
+
+        loop {
+            // A failed check (say, the calendar being unreachable) shouldn't
+            // stop us from trying again later.
+            if let Err(err) = self.check(cli, &data_dir, &mut state).await {
+                tracing::error!("{err:?}");
+            }
+
+            tokio::time::sleep(self.check_every.to_std().unwrap_or_default()).await;
+        }
+    }
+
+    async fn check(&self, cli: &Cli, data_dir: &Path, state: &mut State) -> Result<()> {
+        let start = cli.start();
+        let end = cli.end(start);
+
+        let calendar_due = match state.calendar_fetched {
+            None => true,
+            Some((fetched, until)) => {
+                until < end || fetched.elapsed() >= self.calendar_every.to_std().unwrap_or_default()
+            }
+        };
+
+        let data = fingerprint(data_dir).await?;
+        let data_changed = state.data.as_ref() != Some(&data);
+
+        if !calendar_due && !data_changed {
+            return Ok(());
+        }
+
+        if calendar_due {
+            tracing::debug!("fetching calendar");
+
+            // Mark the attempt before making it, so a calendar outage means
+            // retrying on the calendar schedule rather than every check.
+            state.calendar_fetched = Some((Instant::now(), end));
+            match cli.busy_times(start, end).await {
+                Ok(busy) => state.busy = busy,
+                Err(err) => tracing::warn!("could not fetch calendar, using what we had: {err:?}"),
+            }
+        }
+
+        if data_changed {
+            tracing::debug!("Taskwarrior data changed");
+        }
+
+        let inputs = cli.inputs_with_busy(start, end, state.busy.clone()).await?;
+        state.data = Some(data);
+
+        let scheduler = plan(inputs);
+
+        let changed = match &state.plan {
+            None => true,
+            Some(previous) => differs(previous, &scheduler.commitments, start),
+        };
+        if !changed {
+            tracing::debug!("schedule is the same as before");
+            return Ok(());
+        }
+
+        println!(
+            "\n########## Planned at {} ##########",
+            start.format("%-I:%M %P")
+        );
+        print_plan(&scheduler);
+
+        if let Some(path) = &self.output_json {
+            let json = serde_json::to_vec_pretty(&scheduler.commitments)
+                .wrap_err("could not serialize schedule")?;
+            write_atomically(path, &json).await?;
+        }
+
+        if let Some(path) = &self.output_ics {
+            write_atomically(
+                path,
+                ics::render(&scheduler.commitments, cli.now()).as_bytes(),
+            )
+            .await?;
+        }
+
+        state.plan = Some(scheduler.commitments);
+
+        Ok(())
+    }
+}
+
+/// Whether anything a person would care about changed between two
+/// schedules: tasks moving around or calendar events coming and going.
+fn differs(old: &[Event], new: &[Event], now: DateTime<Tz>) -> bool {
+    let blocked = |events: &[Event]| -> Vec<(DateTime<Tz>, DateTime<Tz>, bool)> {
+        events
+            .iter()
+            .filter(|event| event.what.is_unavailable() && event.end > now)
+            .map(|event| (event.start, event.end, event.what.is_meeting()))
+            .collect()
+    };
+
+    !diff::diff(old, new, now).is_empty() || blocked(old) != blocked(new)
+}
+
+/// The name, modification time, and size of every file in a directory.
+/// Taskwarrior rewrites at least one of these whenever tasks change.
+async fn fingerprint(dir: &Path) -> Result<Vec<(PathBuf, SystemTime, u64)>> {
+    let mut entries = tokio::fs::read_dir(dir)
+        .await
+        .wrap_err_with(|| format!("could not read {}", dir.display()))?;
+
+    let mut out = Vec::new();
+
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .wrap_err_with(|| format!("could not list {}", dir.display()))?
+    {
+        let metadata = entry
+            .metadata()
+            .await
+            .wrap_err_with(|| format!("could not stat {}", entry.path().display()))?;
+
+        if metadata.is_file() {
+            out.push((entry.path(), metadata.modified()?, metadata.len()));
+        }
+    }
+
+    out.sort();
+
+    Ok(out)
+}
+
+/// Write to a temporary file and move it into place, so anything reading the
+/// output never sees half a schedule.
+async fn write_atomically(path: &Path, contents: &[u8]) -> Result<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+
+    tokio::fs::write(&temp, contents)
+        .await
+        .wrap_err_with(|| format!("could not write {}", path.display()))?;
+
+    tokio::fs::rename(&temp, path)
+        .await
+        .wrap_err_with(|| format!("could not move new {} into place", path.display()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::America::Chicago;
+    use task_sched::scheduler::EventData;
+
+    fn at(hour: u32) -> DateTime<Tz> {
+        Chicago.with_ymd_and_hms(2025, 3, 4, hour, 0, 0).unwrap()
+    }
+
+    fn blocked(start: u32, end: u32) -> Event {
+        Event {
+            start: at(start),
+            end: at(end),
+            what: EventData::Blocked,
+        }
+    }
+
+    #[test]
+    fn new_meetings_are_a_change() {
+        assert!(differs(&[], &[blocked(9, 10)], at(8)))
+    }
+
+    #[test]
+    fn past_meetings_are_not_a_change() {
+        assert!(!differs(&[blocked(9, 10)], &[], at(11)))
+    }
+}
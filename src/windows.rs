@@ -0,0 +1,320 @@
+use crate::dates;
+use chrono::{DateTime, NaiveTime};
+use chrono_tz::Tz;
+use color_eyre::eyre::{eyre, Context, Result};
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+/// A named, recurring time of day (e.g. `deep=09:00-12:00`.) Either side can
+/// be left off to mean "from the start of the day" or "until the end of the
+/// day", so `admin=15:00-` covers the rest of the afternoon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub name: String,
+    pub start: Option<NaiveTime>,
+    pub end: Option<NaiveTime>,
+}
+
+impl TimeWindow {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        self.start.map(|start| start <= time).unwrap_or(true)
+            && self.end.map(|end| time < end).unwrap_or(true)
+    }
+}
+
+impl FromStr for TimeWindow {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, range) = s
+            .split_once('=')
+            .ok_or(eyre!("Expected a window like `deep=09:00-12:00`"))?;
+
+        let (start, end) = range
+            .split_once('-')
+            .ok_or(eyre!("Expected a time range like `09:00-12:00`"))?;
+
+        let window = Self {
+            name: name.trim().to_owned(),
+            start: parse_time_of_day(start).wrap_err("could not parse window start")?,
+            end: parse_time_of_day(end).wrap_err("could not parse window end")?,
+        };
+
+        if window.name.is_empty() {
+            return Err(eyre!("Windows need a name"));
+        }
+
+        if let (Some(start), Some(end)) = (window.start, window.end) {
+            if start >= end {
+                return Err(eyre!("Window `{}` ends before it starts", window.name));
+            }
+        }
+
+        Ok(window)
+    }
+}
+
+fn parse_time_of_day(input: &str) -> Result<Option<NaiveTime>> {
+    let input = input.trim();
+
+    if input.is_empty() {
+        return Ok(None);
+    }
+
+    NaiveTime::parse_from_str(input, "%H:%M")
+        .map(Some)
+        .wrap_err_with(|| format!("expected a time like 09:00, got `{input}`"))
+}
+
+/// Maps a tag onto a window name (e.g. `focus=deep`.)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowTag {
+    pub tag: String,
+    pub window: String,
+}
+
+impl FromStr for WindowTag {
+    type Err = color_eyre::Report;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (tag, window) = s
+            .split_once('=')
+            .ok_or(eyre!("Expected a mapping like `focus=deep`"))?;
+
+        Ok(Self {
+            tag: tag.trim().to_owned(),
+            window: window.trim().to_owned(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum WindowMode {
+    /// Schedule tagged tasks inside their windows first, but let them spill
+    /// outside when there's nothing else to do.
+    #[default]
+    Prefer,
+
+    /// Never schedule tagged tasks outside their windows.
+    Restrict,
+}
+
+/// How well a task fits the time it's being considered for. Ordered so that
+/// a better fit compares greater.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Fit {
+    /// The task belongs in some window, but not this one.
+    OutOfWindow,
+
+    /// The task doesn't belong to any window.
+    Neutral,
+
+    /// The task belongs in a window that's open right now.
+    InWindow,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Windows {
+    windows: Vec<TimeWindow>,
+    tags: HashMap<String, HashSet<String>>,
+    mode: WindowMode,
+}
+
+impl Windows {
+    /// Errors if a tag is mapped onto a window that doesn't exist, since
+    /// tasks with that tag could never be in their window.
+    pub fn new(windows: Vec<TimeWindow>, tags: Vec<WindowTag>, mode: WindowMode) -> Result<Self> {
+        let mut out = Self {
+            windows,
+            tags: HashMap::new(),
+            mode,
+        };
+
+        // A window's name is also a tag for it, so `+deep` goes in `deep`
+        // without any extra configuration.
+        for window in &out.windows {
+            out.tags
+                .entry(window.name.clone())
+                .or_default()
+                .insert(window.name.clone());
+        }
+
+        for WindowTag { tag, window } in tags {
+            if !out.windows.iter().any(|w| w.name == window) {
+                return Err(eyre!(
+                    "`{tag}` is mapped onto a window named `{window}`, but there isn't one. Add it with --window {window}=HH:MM-HH:MM."
+                ));
+            }
+
+            out.tags.entry(tag).or_default().insert(window);
+        }
+
+        Ok(out)
+    }
+
+    pub fn mode(&self) -> WindowMode {
+        self.mode
+    }
+
+    /// The next time after `when` that any window opens or closes, or
+    /// `None` if there aren't any windows. Which tasks fit best can only
+    /// change at these times.
+    pub fn next_boundary(&self, when: DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let mut times: Vec<NaiveTime> = self
+            .windows
+            .iter()
+            .flat_map(|window| {
+                [window.start.unwrap_or(NaiveTime::MIN)]
+                    .into_iter()
+                    .chain(window.end)
+            })
+            .collect();
+        times.sort();
+
+        let tz = when.timezone();
+        let today = when.date_naive();
+        let tomorrow = today.succ_opt()?;
+
+        [today, tomorrow]
+            .into_iter()
+            .flat_map(|date| {
+                times
+                    .iter()
+                    .map(move |time| dates::at_local(&tz, date, *time))
+            })
+            .find(|boundary| *boundary > when)
+    }
+
+    pub fn fit(&self, tags: &HashSet<String>, time: NaiveTime) -> Fit {
+        let mut names = tags
+            .iter()
+            .filter_map(|tag| self.tags.get(tag))
+            .flatten()
+            .peekable();
+
+        if names.peek().is_none() {
+            return Fit::Neutral;
+        }
+
+        let open = names.any(|name| {
+            self.windows
+                .iter()
+                .any(|window| &window.name == name && window.contains(time))
+        });
+
+        if open {
+            Fit::InWindow
+        } else {
+            Fit::OutOfWindow
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    fn tags(tags: &[&str]) -> HashSet<String> {
+        tags.iter().map(|t| t.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_window() {
+        assert_eq!(
+            "deep=09:00-12:00".parse::<TimeWindow>().unwrap(),
+            TimeWindow {
+                name: "deep".into(),
+                start: Some(time(9, 0)),
+                end: Some(time(12, 0)),
+            }
+        )
+    }
+
+    #[test]
+    fn parse_open_ended_window() {
+        let window = "admin=15:00-".parse::<TimeWindow>().unwrap();
+
+        assert_eq!(window.start, Some(time(15, 0)));
+        assert_eq!(window.end, None);
+    }
+
+    #[test]
+    fn parse_backwards_window() {
+        assert!("deep=12:00-09:00".parse::<TimeWindow>().is_err())
+    }
+
+    #[test]
+    fn window_is_half_open() {
+        let window = "deep=09:00-12:00".parse::<TimeWindow>().unwrap();
+
+        assert!(window.contains(time(9, 0)));
+        assert!(!window.contains(time(12, 0)));
+    }
+
+    #[test]
+    fn fit_by_window_name() {
+        let windows = Windows::new(
+            vec!["deep=09:00-12:00".parse().unwrap()],
+            Vec::new(),
+            WindowMode::Prefer,
+        )
+        .unwrap();
+
+        assert_eq!(windows.fit(&tags(&["deep"]), time(10, 0)), Fit::InWindow);
+        assert_eq!(windows.fit(&tags(&["deep"]), time(13, 0)), Fit::OutOfWindow);
+        assert_eq!(windows.fit(&tags(&["other"]), time(10, 0)), Fit::Neutral);
+    }
+
+    #[test]
+    fn fit_by_mapped_tag() {
+        let windows = Windows::new(
+            vec!["admin=15:00-".parse().unwrap()],
+            vec!["email=admin".parse().unwrap()],
+            WindowMode::Prefer,
+        )
+        .unwrap();
+
+        assert_eq!(windows.fit(&tags(&["email"]), time(16, 0)), Fit::InWindow);
+        assert_eq!(windows.fit(&tags(&["email"]), time(9, 0)), Fit::OutOfWindow);
+    }
+
+    #[test]
+    fn tags_need_a_window() {
+        let windows = Windows::new(
+            vec!["deep=09:00-12:00".parse().unwrap()],
+            vec!["email=admin".parse().unwrap()],
+            WindowMode::Restrict,
+        );
+
+        assert!(windows.is_err());
+    }
+
+    #[test]
+    fn next_boundary() {
+        use chrono::TimeZone;
+        use chrono_tz::America::Chicago;
+
+        let windows = Windows::new(
+            vec![
+                "deep=09:00-12:00".parse().unwrap(),
+                "admin=15:00-".parse().unwrap(),
+            ],
+            Vec::new(),
+            WindowMode::Prefer,
+        )
+        .unwrap();
+        let at = |d, h, m| Chicago.with_ymd_and_hms(2025, 3, d, h, m, 0).unwrap();
+
+        assert_eq!(windows.next_boundary(at(3, 8, 0)), Some(at(3, 9, 0)));
+        assert_eq!(windows.next_boundary(at(3, 9, 0)), Some(at(3, 12, 0)));
+        assert_eq!(windows.next_boundary(at(3, 13, 30)), Some(at(3, 15, 0)));
+        assert_eq!(windows.next_boundary(at(3, 16, 0)), Some(at(4, 9, 0)));
+        assert_eq!(Windows::default().next_boundary(at(3, 8, 0)), None);
+    }
+}
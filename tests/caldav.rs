@@ -0,0 +1,129 @@
+//! Reading busy times from a local server that answers like a CalDAV one,
+//! with a response recorded from a real server in `tests/fixtures`.
+
+use axum::http::{header, HeaderMap, Method, StatusCode};
+use axum::routing::any;
+use axum::Router;
+use chrono::{Duration, TimeZone};
+use chrono_tz::America::Chicago;
+use task_sched::caldav::CalDav;
+use task_sched::calendar::Status;
+
+const REPORT: &str = include_str!("fixtures/caldav-report.xml");
+
+async fn report(method: Method, headers: HeaderMap, body: String) -> (StatusCode, String) {
+    let authorized = headers
+        .get(header::AUTHORIZATION)
+        .is_some_and(|auth| auth == "Basic bWU6aHVudGVyMg=="); // me:hunter2
+
+    if !authorized {
+        (StatusCode::UNAUTHORIZED, String::new())
+    } else if method.as_str() != "REPORT"
+        || headers.get("Depth").is_none_or(|depth| depth != "1")
+        || !body.contains("calendar-query")
+    {
+        (StatusCode::BAD_REQUEST, String::new())
+    } else {
+        (StatusCode::MULTI_STATUS, REPORT.to_string())
+    }
+}
+
+/// Serve `report` on a free port, returning the calendar's URL.
+async fn server(credentials: &str) -> reqwest::Url {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        axum::serve(listener, Router::new().route("/{*path}", any(report)))
+            .await
+            .unwrap()
+    });
+
+    format!("http://{credentials}@{addr}/dav/calendars/user/me@example.com/Default/")
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn reads_busy_times() {
+    let start = Chicago.with_ymd_and_hms(2025, 3, 3, 0, 0, 0).unwrap();
+    let url = server("me:hunter2").await;
+
+    let busy = CalDav::new(url)
+        .busy_times(start, start + Duration::days(7))
+        .await
+        .unwrap();
+
+    let busy: Vec<_> = busy
+        .iter()
+        .map(|time| {
+            (
+                time.start.with_timezone(&Chicago).to_string(),
+                time.end - time.start,
+                time.status,
+                time.title.as_deref(),
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        busy,
+        vec![
+            (
+                "2025-03-03 09:30:00 CST".to_string(),
+                Duration::minutes(15),
+                Status::Busy,
+                Some("Standup"),
+            ),
+            (
+                "2025-03-05 09:30:00 CST".to_string(),
+                Duration::minutes(15),
+                Status::Busy,
+                Some("Standup"),
+            ),
+            (
+                "2025-03-04 14:00:00 CST".to_string(),
+                Duration::hours(1),
+                Status::OutOfOffice,
+                Some("Dentist & cleaning"),
+            ),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn wrong_password() {
+    let start = Chicago.with_ymd_and_hms(2025, 3, 3, 0, 0, 0).unwrap();
+    let url = server("me:hunter3").await;
+
+    let err = CalDav::new(url)
+        .busy_times(start, start + Duration::days(7))
+        .await
+        .unwrap_err();
+
+    assert!(err.to_string().contains("401"), "{err}");
+}
+
+#[tokio::test]
+async fn errors_leave_out_the_password() {
+    let start = Chicago.with_ymd_and_hms(2025, 3, 3, 0, 0, 0).unwrap();
+
+    // Find a port nothing is listening on.
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+
+    let url = format!("http://me:hunter2@{addr}/dav/calendars/user/me@example.com/Default/")
+        .parse()
+        .unwrap();
+    let err = CalDav::new(url)
+        .busy_times(start, start + Duration::days(7))
+        .await
+        .unwrap_err();
+
+    // The URL is where the password goes, so none of it should show up.
+    for shown in [format!("{err}"), format!("{err:#}"), format!("{err:?}")] {
+        assert!(!shown.contains("hunter2"), "{shown}");
+        assert!(!shown.contains(&addr.to_string()), "{shown}");
+    }
+}
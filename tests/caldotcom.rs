@@ -0,0 +1,229 @@
+//! Talking to a local server that answers like cal.com does, including when
+//! things go wrong.
+
+use axum::extract::{Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{Duration, TimeZone};
+use chrono_tz::America::Chicago;
+use serde_json::json;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use task_sched::caldotcom::{ApiError, CalDotCom, CalendarId};
+
+/// How the server should answer: with a status and body for the first few
+/// requests, then normally. Rate limits send their body as `Retry-After`
+/// instead.
+#[derive(Clone)]
+struct Script {
+    failures: Vec<(StatusCode, &'static str)>,
+    requests: Arc<AtomicUsize>,
+}
+
+fn authorized(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::AUTHORIZATION)
+        .is_some_and(|auth| auth == "Bearer good-token")
+}
+
+fn failure(script: &Script) -> Option<Response> {
+    let i = script.requests.fetch_add(1, Ordering::SeqCst);
+    let (status, body) = script.failures.get(i)?;
+
+    if *status == StatusCode::TOO_MANY_REQUESTS {
+        return Some((*status, [(header::RETRY_AFTER, *body)]).into_response());
+    }
+
+    Some((*status, body.to_string()).into_response())
+}
+
+fn unauthorized() -> Response {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(json!({
+            "status": "error",
+            "error": {"code": "UnauthorizedException", "message": "Invalid API Key"},
+        })),
+    )
+        .into_response()
+}
+
+async fn calendars(State(script): State<Script>, headers: HeaderMap) -> Response {
+    if let Some(response) = failure(&script) {
+        return response;
+    }
+    if !authorized(&headers) {
+        return unauthorized();
+    }
+
+    Json(json!({
+        "status": "success",
+        "data": {
+            "connectedCalendars": [{
+                "credentialId": 7,
+                "calendars": [{"externalId": "me@work.com", "isSelected": true}],
+            }],
+        },
+    }))
+    .into_response()
+}
+
+async fn busy_times(
+    State(script): State<Script>,
+    headers: HeaderMap,
+    Query(params): Query<HashMap<String, String>>,
+) -> Response {
+    if let Some(response) = failure(&script) {
+        return response;
+    }
+    if !authorized(&headers) {
+        return unauthorized();
+    }
+
+    let expected = [
+        ("loggedInUsersTz", "America/Chicago"),
+        ("dateFrom", "2025-03-03"),
+        ("dateTo", "2025-03-04"),
+        ("calendarsToLoad[0][credentialId]", "7"),
+        ("calendarsToLoad[0][externalId]", "me@work.com"),
+    ];
+    for (key, value) in expected {
+        if params.get(key).map(String::as_str) != Some(value) {
+            return (StatusCode::BAD_REQUEST, format!("bad {key}")).into_response();
+        }
+    }
+
+    Json(json!({
+        "status": "success",
+        "data": [{"start": "2025-03-03T16:00:00Z", "end": "2025-03-03T17:00:00Z"}],
+    }))
+    .into_response()
+}
+
+/// Serve the script on a free port, returning a client for it.
+async fn server(token: &str, failures: Vec<(StatusCode, &'static str)>) -> (CalDotCom, Script) {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let script = Script {
+        failures,
+        requests: Arc::new(AtomicUsize::new(0)),
+    };
+
+    let app = Router::new()
+        .route("/v2/calendars", get(calendars))
+        .route("/v2/calendars/busy-times", get(busy_times))
+        .with_state(script.clone());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let client =
+        CalDotCom::new(token.into()).with_base_url(format!("http://{addr}/v2/").parse().unwrap());
+
+    (client, script)
+}
+
+fn api_error(err: &color_eyre::Report) -> &ApiError {
+    err.downcast_ref::<ApiError>()
+        .unwrap_or_else(|| panic!("not an API error: {err:?}"))
+}
+
+#[tokio::test]
+async fn reads_calendars_and_busy_times() {
+    let (client, _) = server("good-token", Vec::new()).await;
+    let start = Chicago.with_ymd_and_hms(2025, 3, 3, 9, 0, 0).unwrap();
+
+    let calendars = client.calendars().await.unwrap().data;
+    let ids: Vec<CalendarId> = calendars.all().map(|(id, _)| id).collect();
+    let busy = client
+        .busy_times(&ids, start, start + Duration::days(1))
+        .await
+        .unwrap()
+        .data;
+
+    assert_eq!(busy.len(), 1);
+    assert_eq!(busy[0].start, start.to_utc() + Duration::hours(1));
+}
+
+#[tokio::test]
+async fn bad_token() {
+    let (client, script) = server("expired-token", Vec::new()).await;
+
+    let err = client.calendars().await.unwrap_err();
+
+    assert_eq!(
+        api_error(&err),
+        &ApiError::Unauthorized {
+            message: Some("Invalid API Key".into())
+        }
+    );
+    assert!(format!("{err:#}").contains("didn't accept the API token (Invalid API Key)"));
+    assert_eq!(
+        script.requests.load(Ordering::SeqCst),
+        1,
+        "auth errors aren't retried"
+    );
+}
+
+#[tokio::test]
+async fn rate_limited() {
+    let (client, script) = server("good-token", vec![(StatusCode::TOO_MANY_REQUESTS, "30")]).await;
+
+    let err = client.calendars().await.unwrap_err();
+
+    assert_eq!(
+        api_error(&err),
+        &ApiError::RateLimited {
+            retry_after: Some(std::time::Duration::from_secs(30))
+        }
+    );
+    assert_eq!(
+        script.requests.load(Ordering::SeqCst),
+        1,
+        "long rate limits aren't waited out"
+    );
+}
+
+#[tokio::test]
+async fn waits_out_short_rate_limits() {
+    let (client, script) = server("good-token", vec![(StatusCode::TOO_MANY_REQUESTS, "1")]).await;
+
+    let started = std::time::Instant::now();
+    let calendars = client.calendars().await.unwrap().data;
+
+    assert_eq!(calendars.all().count(), 1);
+    assert_eq!(script.requests.load(Ordering::SeqCst), 2);
+    assert!(started.elapsed() >= std::time::Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn server_errors() {
+    let body = r#"{"status":"error","error":{"message":"Database unavailable"}}"#;
+    let (client, _) = server(
+        "good-token",
+        vec![(StatusCode::SERVICE_UNAVAILABLE, body); 3],
+    )
+    .await;
+
+    let err = client.calendars().await.unwrap_err();
+
+    assert_eq!(
+        api_error(&err),
+        &ApiError::Server {
+            status: StatusCode::SERVICE_UNAVAILABLE,
+            message: Some("Database unavailable".into())
+        }
+    );
+}
+
+#[tokio::test]
+async fn retries_server_errors() {
+    let (client, script) = server("good-token", vec![(StatusCode::BAD_GATEWAY, "")]).await;
+
+    let calendars = client.calendars().await.unwrap().data;
+
+    assert_eq!(calendars.all().count(), 1);
+    assert_eq!(script.requests.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,11 @@
+//! Building inputs for the scheduler without going through Taskwarrior.
+
+use chrono::{DateTime, TimeZone};
+use chrono_tz::{America::Chicago, Tz};
+pub use task_sched::test_support::TaskBuilder;
+
+/// 8:00 on Monday, March 3, 2025 in Chicago. Daylight saving starts the
+/// Sunday after, so plans that run a week cross it.
+pub fn monday_morning() -> DateTime<Tz> {
+    Chicago.with_ymd_and_hms(2025, 3, 3, 8, 0, 0).unwrap()
+}
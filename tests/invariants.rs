@@ -0,0 +1,433 @@
+mod common;
+
+use chrono::{DateTime, Datelike, Duration, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use proptest::prelude::*;
+use task_sched::budget::{Budget, Period, Selector};
+use task_sched::calendar::{BlockedTime, Status};
+use task_sched::windows::{TimeWindow, WindowMode, Windows};
+use task_sched::{plan, EventData, Inputs, Scheduler, Task};
+
+const DAYS: i64 = 7;
+
+#[derive(Debug, Clone)]
+struct TaskSpec {
+    estimate: i64,
+    urgency: f64,
+    wait: Option<i64>,
+    depends: u8,
+    /// Bit 0 puts the task in the `deep` window, bit 1 under the budget.
+    tags: u8,
+    notbefore: Option<u32>,
+    notafter: Option<u32>,
+    weekdays: Option<u8>,
+    /// Pinned to this day of the plan and quarter hour after 8:00.
+    fixed: Option<(i64, i64)>,
+}
+
+prop_compose! {
+    fn task_spec()(
+        estimate in 5..240i64,
+        urgency in 0.0..20.0f64,
+        wait in prop::option::weighted(0.3, 0..DAYS * 24 * 60),
+        depends in any::<u8>(),
+        tags in 0..4u8,
+        notbefore in prop::option::weighted(0.2, 0..24u32),
+        notafter in prop::option::weighted(0.2, 0..24u32),
+        weekdays in prop::option::weighted(0.2, 1..128u8),
+        fixed in prop::option::weighted(0.15, (0..DAYS, 0..40i64)),
+    ) -> TaskSpec {
+        TaskSpec { estimate, urgency, wait, depends, tags, notbefore, notafter, weekdays, fixed }
+    }
+}
+
+/// The window and budget every plan is made with.
+#[derive(Debug, Clone)]
+struct Rules {
+    window: TimeWindow,
+    mode: WindowMode,
+    budget: Budget,
+}
+
+prop_compose! {
+    fn rules()(
+        start in 8..16u32,
+        length in 1..4u32,
+        restrict in any::<bool>(),
+        limit in 15..300i64,
+        weekly in prop::bool::weighted(0.3),
+    ) -> Rules {
+        Rules {
+            window: TimeWindow {
+                name: "deep".into(),
+                start: NaiveTime::from_hms_opt(start, 0, 0),
+                end: NaiveTime::from_hms_opt(start + length, 0, 0),
+            },
+            mode: if restrict { WindowMode::Restrict } else { WindowMode::Prefer },
+            budget: Budget {
+                applies_to: Selector::Tag("budgeted".into()),
+                limit: Duration::minutes(limit),
+                period: if weekly { Period::Week } else { Period::Day },
+            },
+        }
+    }
+}
+
+fn status() -> impl Strategy<Value = Status> {
+    prop_oneof![
+        4 => Just(Status::Busy),
+        1 => Just(Status::Tentative),
+        1 => Just(Status::OutOfOffice),
+    ]
+}
+
+prop_compose! {
+    fn busy_time()(
+        start in 0..DAYS * 24 * 60,
+        length in 15..180i64,
+        status in status(),
+        title in prop::option::of("[A-Z][a-z]{2,8}"),
+    ) -> BlockedTime {
+        let start = common::monday_morning().to_utc() + Duration::minutes(start);
+
+        BlockedTime {
+            start,
+            end: start + Duration::minutes(length),
+            status,
+            title,
+        }
+    }
+}
+
+/// Turn specs into tasks. Each task can only depend on the ones before it
+/// (picked out by the bits in `depends`) so there are never any cycles.
+fn tasks(specs: &[TaskSpec]) -> Vec<Task> {
+    specs
+        .iter()
+        .enumerate()
+        .map(|(i, spec)| {
+            let mut task = common::TaskBuilder::new(&format!("task-{i}"))
+                .with_description(&format!("Task {i}"))
+                .with_estimate(Duration::minutes(spec.estimate))
+                .with_urgency(spec.urgency)
+                .build();
+
+            if spec.tags & 1 != 0 {
+                task.tags.insert("deep".into());
+            }
+            if spec.tags & 2 != 0 {
+                task.tags.insert("budgeted".into());
+            }
+
+            task.notbefore = spec
+                .notbefore
+                .and_then(|h| NaiveTime::from_hms_opt(h, 0, 0));
+            task.notafter = spec.notafter.and_then(|h| NaiveTime::from_hms_opt(h, 0, 0));
+            task.weekdays = (0..7)
+                .filter(|day| spec.weekdays.is_some_and(|bits| bits & (1 << day) != 0))
+                .map(|day| Weekday::try_from(day).unwrap())
+                .collect();
+
+            // Pinned tasks keep their slot no matter what, so they don't
+            // wait or depend on anything here. Tasks can still depend on
+            // them.
+            if let Some((day, quarter)) = spec.fixed {
+                let at = common::monday_morning()
+                    + Duration::days(day)
+                    + Duration::minutes(quarter * 15);
+                task.scheduled = Some(at.to_utc());
+                task.tags.insert("fixed".into());
+                return task;
+            }
+
+            task.wait = spec
+                .wait
+                .map(|wait| common::monday_morning().to_utc() + Duration::minutes(wait));
+
+            task.depends = (0..i.min(8))
+                .filter(|dep| spec.depends & (1 << dep) != 0)
+                .map(|dep| format!("task-{dep}"))
+                .collect();
+
+            task
+        })
+        .collect()
+}
+
+fn schedule(specs: &[TaskSpec], busy: Vec<BlockedTime>, rules: &Rules) -> (Vec<Task>, Scheduler) {
+    let start = common::monday_morning();
+    let tasks = tasks(specs);
+
+    let scheduler = plan(
+        Inputs::new(start, start + Duration::days(DAYS))
+            .with_busy(busy)
+            .with_windows(Windows::new(vec![rules.window.clone()], Vec::new(), rules.mode).unwrap())
+            .with_budgets(vec![rules.budget.clone()])
+            .with_tasks(tasks.clone()),
+    );
+
+    (tasks, scheduler)
+}
+
+fn is_fixed(task: &Task) -> bool {
+    task.tags.contains("fixed")
+}
+
+/// Whether an event is time taken by something the scheduler didn't place
+/// itself: calendar events, off hours, or pinned tasks.
+fn is_placed_for_us(tasks: &[Task], event: &task_sched::Event) -> bool {
+    match &event.what {
+        EventData::Task { uuid, .. } => tasks
+            .iter()
+            .any(|task| &task.uuid == uuid && is_fixed(task)),
+        what => what.is_unavailable(),
+    }
+}
+
+/// The budget period that `when` is in, as its first day.
+fn period(rules: &Rules, when: DateTime<Tz>) -> chrono::NaiveDate {
+    let date = when.date_naive();
+    match rules.budget.period {
+        Period::Day => date,
+        Period::Week => date - Duration::days(date.weekday().num_days_from_monday().into()),
+    }
+}
+
+/// How much time the scheduler gave budgeted tasks in each period.
+fn budgeted(
+    tasks: &[Task],
+    scheduler: &Scheduler,
+    rules: &Rules,
+) -> Vec<(chrono::NaiveDate, Duration)> {
+    let mut totals: Vec<(chrono::NaiveDate, Duration)> = Vec::new();
+
+    for task in tasks
+        .iter()
+        .filter(|task| task.tags.contains("budgeted") && !is_fixed(task))
+    {
+        for event in scheduled_for(scheduler, &task.uuid) {
+            let period = period(rules, event.start);
+            match totals.iter_mut().find(|(p, _)| *p == period) {
+                Some((_, total)) => *total += event.duration(),
+                None => totals.push((period, event.duration())),
+            }
+        }
+    }
+
+    totals
+}
+
+fn scheduled_for<'a>(
+    scheduler: &'a Scheduler,
+    uuid: &'a str,
+) -> impl Iterator<Item = &'a task_sched::Event> {
+    scheduler
+        .commitments
+        .iter()
+        .filter(move |event| matches!(&event.what, EventData::Task { uuid: id, .. } if id == uuid))
+}
+
+proptest! {
+    #[test]
+    fn work_never_overlaps_anything(
+        specs in prop::collection::vec(task_spec(), 0..8),
+        busy in prop::collection::vec(busy_time(), 0..10),
+        rules in rules(),
+    ) {
+        let (tasks, scheduler) = schedule(&specs, busy, &rules);
+
+        // Pinned tasks can land on top of meetings (we warn about that) but
+        // nothing the scheduler places should ever overlap anything.
+        for (i, event) in scheduler.commitments.iter().enumerate() {
+            if is_placed_for_us(&tasks, event) {
+                continue;
+            }
+
+            prop_assert!(event.start < event.end, "{event:?} is empty");
+            prop_assert!(event.start >= scheduler.start() && event.end <= scheduler.end());
+
+            for (j, other) in scheduler.commitments.iter().enumerate() {
+                if i != j {
+                    prop_assert!(
+                        event.end <= other.start || other.end <= event.start,
+                        "{event:?} overlaps {other:?}",
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tasks_wait(
+        specs in prop::collection::vec(task_spec(), 0..8),
+        busy in prop::collection::vec(busy_time(), 0..10),
+        rules in rules(),
+    ) {
+        let (tasks, scheduler) = schedule(&specs, busy, &rules);
+
+        for task in &tasks {
+            let Some(wait) = task.wait else { continue };
+
+            for event in scheduled_for(&scheduler, &task.uuid) {
+                prop_assert!(event.start.to_utc() >= wait, "{} started before {wait}", task.uuid);
+            }
+        }
+    }
+
+    #[test]
+    fn dependencies_come_first(
+        specs in prop::collection::vec(task_spec(), 0..8),
+        busy in prop::collection::vec(busy_time(), 0..10),
+        rules in rules(),
+    ) {
+        let (tasks, scheduler) = schedule(&specs, busy, &rules);
+
+        for task in &tasks {
+            for event in scheduled_for(&scheduler, &task.uuid) {
+                for dep in &task.depends {
+                    let done = scheduler.completed_at(dep);
+
+                    prop_assert!(
+                        done.is_some_and(|done| done <= event.start),
+                        "{} was scheduled at {} but {dep} finished at {done:?}",
+                        task.uuid,
+                        event.start,
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tasks_get_their_estimate_at_most(
+        specs in prop::collection::vec(task_spec(), 0..8),
+        busy in prop::collection::vec(busy_time(), 0..10),
+        rules in rules(),
+    ) {
+        let (tasks, scheduler) = schedule(&specs, busy, &rules);
+
+        for task in &tasks {
+            let scheduled = scheduled_for(&scheduler, &task.uuid)
+                .fold(Duration::zero(), |total, event| total + event.duration());
+            let estimate = task.estimate.unwrap();
+
+            prop_assert!(scheduled <= estimate, "{} got {scheduled} of {estimate}", task.uuid);
+            prop_assert_eq!(scheduled + scheduler.remaining_time(&task.uuid).unwrap(), estimate);
+        }
+    }
+
+    #[test]
+    fn pinned_tasks_keep_their_slot(
+        specs in prop::collection::vec(task_spec(), 0..8),
+        busy in prop::collection::vec(busy_time(), 0..10),
+        rules in rules(),
+    ) {
+        let (tasks, scheduler) = schedule(&specs, busy, &rules);
+
+        for task in tasks.iter().filter(|task| is_fixed(task)) {
+            let events: Vec<_> = scheduled_for(&scheduler, &task.uuid).collect();
+
+            prop_assert_eq!(events.len(), 1, "{} should be pinned once", task.uuid);
+            prop_assert_eq!(events[0].start.to_utc(), task.scheduled.unwrap());
+            prop_assert_eq!(events[0].duration(), task.estimate.unwrap());
+        }
+    }
+
+    #[test]
+    fn tasks_stay_in_their_allowed_times(
+        specs in prop::collection::vec(task_spec(), 0..8),
+        busy in prop::collection::vec(busy_time(), 0..10),
+        rules in rules(),
+    ) {
+        let (tasks, scheduler) = schedule(&specs, busy, &rules);
+
+        for task in tasks.iter().filter(|task| !is_fixed(task)) {
+            for event in scheduled_for(&scheduler, &task.uuid) {
+                let last = event.end - Duration::minutes(1);
+
+                prop_assert!(
+                    task.allowed_at(&event.start) && task.allowed_at(&last),
+                    "{} was scheduled at {}-{} outside its notbefore/notafter/weekdays",
+                    task.uuid,
+                    event.start,
+                    event.end,
+                );
+
+                if rules.mode == WindowMode::Restrict && task.tags.contains("deep") {
+                    prop_assert!(
+                        rules.window.contains(event.start.time()) && rules.window.contains(last.time()),
+                        "{} was scheduled at {}-{} outside its window",
+                        task.uuid,
+                        event.start,
+                        event.end,
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn budgets_are_never_overspent(
+        specs in prop::collection::vec(task_spec(), 0..8),
+        busy in prop::collection::vec(busy_time(), 0..10),
+        rules in rules(),
+    ) {
+        let (tasks, scheduler) = schedule(&specs, busy, &rules);
+
+        for (period, total) in budgeted(&tasks, &scheduler, &rules) {
+            prop_assert!(
+                total <= rules.budget.limit,
+                "budgeted tasks got {total} in the period starting {period}",
+            );
+        }
+    }
+
+    /// If a task got no time at all, there was never free time it could
+    /// have used: whenever the plan is empty, the task was waiting, outside
+    /// its allowed times or window, over budget, or blocked.
+    #[test]
+    fn tasks_get_time_when_there_is_room(
+        specs in prop::collection::vec(task_spec(), 0..8),
+        busy in prop::collection::vec(busy_time(), 0..10),
+        rules in rules(),
+    ) {
+        let (tasks, scheduler) = schedule(&specs, busy, &rules);
+        let spent = budgeted(&tasks, &scheduler, &rules);
+
+        for task in &tasks {
+            if scheduled_for(&scheduler, &task.uuid).next().is_some() {
+                continue;
+            }
+
+            let mut when = scheduler.start();
+            while when < scheduler.end() {
+                let free = !scheduler
+                    .commitments
+                    .iter()
+                    .any(|event| event.start <= when && when < event.end);
+
+                let could_run = task.available_at(when.to_utc())
+                    && task.allowed_at(&when)
+                    && !(rules.mode == WindowMode::Restrict
+                        && task.tags.contains("deep")
+                        && !rules.window.contains(when.time()))
+                    && !(task.tags.contains("budgeted")
+                        && spent
+                            .iter()
+                            .any(|(p, total)| *p == period(&rules, when) && *total >= rules.budget.limit))
+                    && task
+                        .depends
+                        .iter()
+                        .all(|dep| scheduler.completed_at(dep).is_some_and(|done| done <= when));
+
+                prop_assert!(
+                    !(free && could_run),
+                    "{} got no time, but could have had {when}",
+                    task.uuid,
+                );
+
+                when += Duration::minutes(15);
+            }
+        }
+    }
+}
@@ -0,0 +1,166 @@
+//! Plans for fixed inputs, rendered the way the CLI prints them and compared
+//! with files in `tests/snapshots`. If a change to the output is on purpose,
+//! run with `UPDATE_SNAPSHOTS=1` to rewrite them and check the diff.
+
+mod common;
+
+use chrono::{Duration, NaiveTime, TimeZone};
+use chrono_tz::America::Chicago;
+use std::path::Path;
+use task_sched::calendar::{BlockedTime, Status};
+use task_sched::scheduler::Buffers;
+use task_sched::summary::Summary;
+use task_sched::{plan, Inputs, Scheduler};
+
+fn render(scheduler: &Scheduler) -> String {
+    format!("{scheduler}{}", Summary::new(scheduler))
+}
+
+fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(format!("{name}.txt"));
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|_| {
+        panic!(
+            "no snapshot at {}. Run with UPDATE_SNAPSHOTS=1 to make one.",
+            path.display()
+        )
+    });
+
+    assert!(
+        expected == actual,
+        "{name} changed. Run with UPDATE_SNAPSHOTS=1 if that's on purpose.\n\n--- expected\n{expected}\n--- actual\n{actual}",
+    );
+}
+
+fn meeting(day: u32, hour: u32, minute: u32, length: i64, title: Option<&str>) -> BlockedTime {
+    let start = Chicago
+        .with_ymd_and_hms(2025, 3, day, hour, minute, 0)
+        .unwrap()
+        .to_utc();
+
+    BlockedTime {
+        start,
+        end: start + Duration::minutes(length),
+        status: Status::Busy,
+        title: title.map(String::from),
+    }
+}
+
+#[test]
+fn day_around_meetings() {
+    let start = common::monday_morning();
+
+    let scheduler = plan(
+        Inputs::new(start, start + Duration::days(1))
+            .with_busy(vec![
+                meeting(3, 10, 0, 30, Some("Standup")),
+                meeting(3, 10, 30, 60, Some("Design review")),
+                meeting(3, 14, 0, 45, None),
+            ])
+            .with_buffers(Buffers {
+                before: Duration::minutes(5),
+                after: Duration::minutes(10),
+                min_meeting: Duration::minutes(30),
+            })
+            .with_tasks(vec![
+                common::TaskBuilder::new("a")
+                    .with_description("Write the proposal")
+                    .with_estimate(Duration::hours(3))
+                    .with_urgency(12.0)
+                    .build(),
+                common::TaskBuilder::new("b")
+                    .with_description("Review pull requests")
+                    .with_estimate(Duration::minutes(45))
+                    .with_urgency(8.0)
+                    .build(),
+                common::TaskBuilder::new("c")
+                    .with_description("Answer email")
+                    .with_estimate(Duration::minutes(20))
+                    .with_urgency(8.0)
+                    .build(),
+                common::TaskBuilder::new("d")
+                    .with_description("Plan the offsite")
+                    .with_estimate(Duration::hours(6))
+                    .with_urgency(2.0)
+                    .build(),
+            ]),
+    );
+
+    assert_snapshot("day_around_meetings", &render(&scheduler));
+}
+
+#[test]
+fn waiting_and_dependencies() {
+    let start = common::monday_morning();
+
+    let mut draft = common::TaskBuilder::new("draft")
+        .with_description("Draft the talk")
+        .with_estimate(Duration::hours(2))
+        .with_urgency(5.0)
+        .build();
+    draft.wait = Some(
+        Chicago
+            .with_ymd_and_hms(2025, 3, 3, 13, 0, 0)
+            .unwrap()
+            .to_utc(),
+    );
+
+    let mut rehearse = common::TaskBuilder::new("rehearse")
+        .with_description("Rehearse the talk")
+        .with_estimate(Duration::hours(1))
+        .with_urgency(20.0)
+        .build();
+    rehearse.depends = ["draft".to_string()].into();
+
+    let mut standup = common::TaskBuilder::new("standup")
+        .with_description("Prep for standup")
+        .with_estimate(Duration::minutes(15))
+        .with_urgency(3.0)
+        .build();
+    standup.notbefore = NaiveTime::from_hms_opt(9, 0, 0);
+    standup.notafter = NaiveTime::from_hms_opt(9, 30, 0);
+
+    let scheduler = plan(
+        Inputs::new(start, start + Duration::days(2)).with_tasks(vec![
+            draft,
+            rehearse,
+            standup,
+            common::TaskBuilder::new("slides")
+                .with_description("Make slides")
+                .with_estimate(Duration::hours(4))
+                .with_urgency(1.0)
+                .build(),
+        ]),
+    );
+
+    assert_snapshot("waiting_and_dependencies", &render(&scheduler));
+}
+
+#[test]
+fn more_work_than_time() {
+    let start = common::monday_morning();
+
+    let scheduler = plan(
+        Inputs::new(start, start + Duration::days(1)).with_tasks(vec![
+            common::TaskBuilder::new("big")
+                .with_description("Migrate the database")
+                .with_estimate(Duration::hours(10))
+                .with_urgency(9.0)
+                .build(),
+            common::TaskBuilder::new("small")
+                .with_description("Update the changelog")
+                .with_estimate(Duration::minutes(30))
+                .with_urgency(4.0)
+                .build(),
+        ]),
+    );
+
+    assert_snapshot("more_work_than_time", &render(&scheduler));
+}